@@ -8,7 +8,9 @@
 /// [Polonius](https://github.com/rust-lang-nursery/polonius/blob/master/src/facts.rs)
 /// source code.
 
+use crate::configuration;
 use csv::ReaderBuilder;
+use log::debug;
 use regex::Regex;
 use rustc::mir;
 use rustc_data_structures::indexed_vec::Idx;
@@ -122,6 +124,12 @@ pub struct Point {
     pub typ: PointType,
 }
 
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}[{}]:{:?}", self.location.block, self.location.statement_index, self.typ)
+    }
+}
+
 impl FromStr for Point {
 
     type Err = ();
@@ -180,6 +188,9 @@ impl<SourceType, IndexType> InternerTable<SourceType, IndexType>
     fn get_index(&self, element: &SourceType) -> IndexType {
         self.index_elements[element]
     }
+    fn find_index(&self, element: &SourceType) -> Option<IndexType> {
+        self.index_elements.get(element).cloned()
+    }
     fn get_element(&self, index: IndexType) -> &SourceType {
         let index: usize = index.into();
         &self.interned_elements[index]
@@ -202,6 +213,12 @@ impl Interner {
         self.points.get_index(point)
     }
 
+    /// Like `get_point_index`, but returns `None` if the point does not
+    /// appear in any of the loaded facts.
+    pub fn find_point_index(&self, point: &Point) -> Option<PointIndex> {
+        self.points.find_index(point)
+    }
+
     pub fn get_point(&self, index: PointIndex) -> &Point {
         self.points.get_element(index)
     }
@@ -311,6 +328,10 @@ fn load_facts<F: DeserializeOwned, T>(interner: &mut Interner, facts_dir: &Path,
     where
         Interner: InternTo<F, T>
 {
+    if !configuration::dump_load_relation(facts_type) {
+        debug!("Skipping loading of disabled relation: {}", facts_type);
+        return Vec::new();
+    }
     load_facts_from_file(facts_dir, facts_type)
         .into_iter()
         .map(|fact| Interner::intern(interner, fact))
@@ -3,9 +3,12 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
 use std::sync::RwLock;
 use std::env;
 use config::{Config, Environment, File};
+use polonius_engine::Algorithm;
 
 lazy_static! {
     // Is this RwLock<..> necessary?
@@ -13,14 +16,7 @@ lazy_static! {
         let mut settings = Config::default();
 
         // 1. Default values
-        settings.set_default("LOG_DIR", "./log/").unwrap();
-        settings.set_default::<Option<String>>("DUMP_MIR_PROC", None).unwrap();
-        settings.set_default("DUMP_MIR_INFO", true).unwrap();
-        settings.set_default("DUMP_SHOW_TEMP_VARIABLES", true).unwrap();
-        settings.set_default("DUMP_SHOW_STATEMENT_INDICES", true).unwrap();
-        settings.set_default("DUMP_DEBUG_INFO", false).unwrap();
-        settings.set_default("TEST", false).unwrap();
-        settings.set_default("FULL_COMPILATION", true).unwrap();
+        set_defaults(&mut settings);
 
         // 2. Override with the optional TOML file "mir_dump.toml" (if there is any)
         settings.merge(
@@ -41,6 +37,300 @@ lazy_static! {
 	});
 }
 
+/// Set the default values of all settings.
+fn set_defaults(settings: &mut Config) {
+    settings.set_default("LOG_DIR", "./log/").unwrap();
+    settings.set_default("DUMP_INIT_SCRIPT", "").unwrap();
+    settings.set_default::<Option<String>>("DUMP_MIR_PROC", None).unwrap();
+    settings.set_default("DUMP_MIR_PROC_REGEX", "").unwrap();
+    settings.set_default("DUMP_ALL_FUNCTIONS", true).unwrap();
+    settings.set_default("DUMP_INCLUDE_CLOSURES", false).unwrap();
+    settings.set_default("DUMP_EXCLUDE_PROCS", "").unwrap();
+    settings.set_default("DUMP_EXCLUDE_SUFFIXES", "__spec").unwrap();
+    settings.set_default("DUMP_EXCLUDE_PREFIXES", "").unwrap();
+    settings.set_default("DUMP_QUIET_PATTERNS", "").unwrap();
+    settings.set_default("DUMP_MIR_INFO", true).unwrap();
+    settings.set_default("DUMP_MIR_PHASE", "validated").unwrap();
+    settings.set_default("POLONIUS_ALGORITHM", "Naive").unwrap();
+    settings.set_default("DUMP_VERIFY_ALGORITHMS", false).unwrap();
+    settings.set_default("DUMP_OUTPUT_FORMATS", "dot").unwrap();
+    settings.set_default("DUMP_OUTPUT_SVG", false).unwrap();
+    settings.set_default("DUMP_OUTPUT_PNG", false).unwrap();
+    settings.set_default("DUMP_SHOW_BB_NAVIGATION", false).unwrap();
+    settings.set_default("DUMP_HTML_TEMPLATE", "").unwrap();
+    settings.set_default("DUMP_ADD_FILE_HEADER", false).unwrap();
+    settings.set_default("DUMP_GRAPH_TITLE", "").unwrap();
+    settings.set_default("DUMP_GRAPH_DESCRIPTION", "").unwrap();
+    settings.set_default("DUMP_SHOW_METADATA", false).unwrap();
+    settings.set_default("DUMP_NODE_STYLE", "record").unwrap();
+    settings.set_default("DUMP_GRAPH_DIRECTION", "TB").unwrap();
+    settings.set_default("DUMP_NODE_ID_FORMAT", "name").unwrap();
+    settings.set_default("DUMP_NODE_FONTNAME", "").unwrap();
+    settings.set_default("DUMP_NODE_FONTSIZE", "").unwrap();
+    settings.set_default("DUMP_EDGE_FONTSIZE", "").unwrap();
+    settings.set_default("DUMP_SWITCH_DIAMOND", false).unwrap();
+    settings.set_default("DUMP_CLUSTER_LOOPS", false).unwrap();
+    settings.set_default("DUMP_SHOW_EDGE_LABELS", false).unwrap();
+    settings.set_default("DUMP_EDGE_STYLES", "unwind=color=red;imaginary=style=\"dashed\";back=color=blue,style=bold").unwrap();
+    settings.set_default("DUMP_SHOW_BORROWCK_RESULT", false).unwrap();
+    settings.set_default("DUMP_ANIMATE_SUBSET", false).unwrap();
+    settings.set_default("DUMP_TYPE_PRECISION", "full").unwrap();
+    settings.set_default("DUMP_PRETTY_TYPES", true).unwrap();
+    settings.set_default("DUMP_MAX_TYPE_DEPTH", 0).unwrap();
+    settings.set_default("DUMP_SHOW_TEMP_VARIABLES", true).unwrap();
+    settings.set_default("DUMP_SHOW_ALL_LOCALS", false).unwrap();
+    settings.set_default("DUMP_MAX_CELL_CHARS", 80).unwrap();
+    settings.set_default("DUMP_MIR_SUBSTS", true).unwrap();
+    settings.set_default("DUMP_MIR_SUBSTS_TRUNCATE", 0).unwrap();
+    settings.set_default("DUMP_MAX_BB_PER_FILE", 50).unwrap();
+    settings.set_default("DUMP_ESCAPE_UNICODE", false).unwrap();
+    settings.set_default("DUMP_SHOW_STATEMENT_INDICES", true).unwrap();
+    settings.set_default("DUMP_HIGHLIGHT_BB_BOUNDARIES", false).unwrap();
+    settings.set_default("DUMP_SHOW_CONFIG_NODE", false).unwrap();
+    settings.set_default("DUMP_HIGHLIGHT_RAW_CASTS", false).unwrap();
+    settings.set_default("DUMP_STORAGE_DISPLAY", "show").unwrap();
+    settings.set_default("DUMP_SHOW_PREDECESSORS", false).unwrap();
+    settings.set_default("DUMP_AGGREGATE_IDENTICAL", false).unwrap();
+    settings.set_default("DUMP_SHOW_START_POINTS", true).unwrap();
+    settings.set_default("DUMP_SHOW_MID_POINTS", true).unwrap();
+    settings.set_default("DUMP_INIT_DISPLAY", "full").unwrap();
+    settings.set_default("DUMP_SHOW_UNINITIALIZED", false).unwrap();
+    settings.set_default("DUMP_SHOW_MAYBE_INITIALIZED", false).unwrap();
+    settings.set_default("DUMP_SHOW_MOVE_PATHS", false).unwrap();
+    settings.set_default("DUMP_SHOW_LOAN_INTERVALS", false).unwrap();
+    settings.set_default("DUMP_EXPAND_REGION_NAMES", false).unwrap();
+    settings.set_default("DUMP_MIN_REGION_IMPORTANCE", 1).unwrap();
+    settings.set_default("DUMP_MAX_REGIONS_PER_CELL", 10).unwrap();
+    settings.set_default("DUMP_HIDE_UNIVERSAL_REGIONS", false).unwrap();
+    settings.set_default("DUMP_SHOW_REGION_KINDS", false).unwrap();
+    settings.set_default("DUMP_SHOW_LOAN_SOURCES", false).unwrap();
+    settings.set_default("DUMP_SHOW_LOAN_SUMMARY", false).unwrap();
+    settings.set_default("DUMP_SHOW_LOAN_LOCATIONS", true).unwrap();
+    settings.set_default("DUMP_SHOW_LOAN_KINDS", true).unwrap();
+    settings.set_default("DUMP_SHOW_CONFLICTING_LOANS", false).unwrap();
+    settings.set_default("DUMP_SHOW_DYING_LOANS", false).unwrap();
+    settings.set_default("DUMP_HIDE_ASCRIBE_USER_TYPE", false).unwrap();
+    settings.set_default("DUMP_STRIP_PATH_PREFIX", "").unwrap();
+    settings.set_default("DUMP_SHOW_FULL_PATHS", true).unwrap();
+    settings.set_default("DUMP_REDACT_PATHS", false).unwrap();
+    settings.set_default("DUMP_REDACT_FUNCTION_NAMES", false).unwrap();
+    settings.set_default("DUMP_MIN_BASIC_BLOCKS", 1).unwrap();
+    settings.set_default("DUMP_MIN_LOAN_COUNT", 0).unwrap();
+    settings.set_default("DUMP_SKIP_TRIVIAL", false).unwrap();
+    settings.set_default("DUMP_TRIVIAL_STUB", false).unwrap();
+    settings.set_default("DUMP_LOAD_BORROW_REGION", true).unwrap();
+    settings.set_default("DUMP_LOAD_UNIVERSAL_REGION", true).unwrap();
+    settings.set_default("DUMP_LOAD_CFG_EDGE", true).unwrap();
+    settings.set_default("DUMP_LOAD_KILLED", true).unwrap();
+    settings.set_default("DUMP_LOAD_OUTLIVES", true).unwrap();
+    settings.set_default("DUMP_LOAD_REGION_LIVE_AT", true).unwrap();
+    settings.set_default("DUMP_LOAD_INVALIDATES", true).unwrap();
+    settings.set_default("DUMP_CATCH_PANICS", false).unwrap();
+    settings.set_default("DUMP_VERIFY_CFG_EDGES", false).unwrap();
+    settings.set_default("DUMP_GENERATE_REPRO", false).unwrap();
+    settings.set_default("DUMP_EXPORT_TIMELINE", false).unwrap();
+    settings.set_default("DUMP_EXPORT_REGION_CONSTRAINTS", false).unwrap();
+    settings.set_default("DUMP_EXPORT_FACTS_CSV", false).unwrap();
+    settings.set_default("DUMP_SUMMARY_FILE", "mir-dump-summary.json").unwrap();
+    settings.set_default("DUMP_DEBUG_INFO", false).unwrap();
+    settings.set_default("TEST", false).unwrap();
+    settings.set_default("FULL_COMPILATION", true).unwrap();
+}
+
+/// The shape used for the basic block nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeStyle {
+    /// A record with an HTML table label.
+    Record,
+    /// A box with a plain text label.
+    Box,
+    /// An ellipse with a plain text label.
+    Ellipse,
+}
+
+/// Which version of MIR should be dumped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirPhase {
+    /// MIR as seen by the borrow checker (`mir_validated`).
+    Validated,
+    /// MIR after all optimization passes (`optimized_mir`). The Polonius
+    /// facts refer to the validated MIR, so they are not shown.
+    Optimized,
+}
+
+/// How are the basic block nodes identified in the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeIdFormat {
+    /// The name of the basic block, for example `bb0`.
+    Name,
+    /// The index of the basic block, for example `0`.
+    Index,
+    /// The function path followed by the name, for example `fn_foo_bb0`.
+    Path,
+}
+
+/// The formats in which the MIR of a function can be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A graphviz graph, `graph.dot`.
+    Dot,
+    /// A machine-readable description of the blocks, `graph.json`.
+    Json,
+    /// A standalone web page with one table per block, `graph.html`.
+    Html,
+    /// Plain text with one line per statement, `graph.txt`.
+    Text,
+}
+
+impl OutputFormat {
+    /// The extension of the file written in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Dot => "dot",
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+            OutputFormat::Text => "txt",
+        }
+    }
+}
+
+/// How are the `StorageLive` and `StorageDead` statements displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageDisplay {
+    /// The statements are not shown.
+    Hide,
+    /// The statements are shown as they are.
+    Show,
+    /// The statements are shown as compact scope hints.
+    Hint,
+}
+
+/// How are the definitely initialized places displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitDisplay {
+    /// The whole set of places.
+    Full,
+    /// The places that were added or removed by the statement.
+    Diff,
+    /// The number of places.
+    Count,
+}
+
+/// How detailed should the displayed types be?
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypePrecision {
+    /// Fully qualified paths as printed by rustc.
+    Full,
+    /// Paths without the crate prefix.
+    Short,
+    /// Only the last two segments of each path.
+    Abbrev,
+}
+
+/// Split a comma-separated list, ignoring empty entries.
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// The settings whose values contain function names, which are hidden when
+/// `DUMP_REDACT_FUNCTION_NAMES` is set.
+pub const FUNCTION_NAME_SETTINGS: &[&str] = &[
+    "DUMP_MIR_PROC",
+    "DUMP_MIR_PROC_REGEX",
+    "DUMP_EXCLUDE_PROCS",
+    "DUMP_EXCLUDE_SUFFIXES",
+    "DUMP_EXCLUDE_PREFIXES",
+    "DUMP_QUIET_PATTERNS",
+];
+
+/// The settings whose values differ from their defaults, sorted by their
+/// names.
+pub fn non_default_settings() -> Vec<(String, String)> {
+    let mut defaults = Config::default();
+    set_defaults(&mut defaults);
+    let defaults = defaults.collect().unwrap();
+    let mut settings: Vec<_> = SETTINGS.read().unwrap()
+        .collect()
+        .unwrap()
+        .into_iter()
+        .map(|(key, value)| (key, value.into_str().unwrap_or_default()))
+        .filter(|(key, value)| {
+            defaults.get(key).map(|default| default.clone().into_str().unwrap_or_default()) !=
+                Some(value.clone())
+        })
+        .map(|(key, value)| (key.to_uppercase(), value))
+        .collect();
+    settings.sort();
+    settings
+}
+
+/// Check that all settings can be converted to the types of their default
+/// values, that the settings with a fixed set of values have one of them,
+/// and that there are no unknown settings. The errors name the
+/// environment variables that set the invalid values, for example
+/// `MIR_DUMP_DUMP_DEBUG_INFO: expected bool, got 'maybe'`.
+pub fn validate() -> Result<(), Vec<String>> {
+    let mut defaults = Config::default();
+    set_defaults(&mut defaults);
+    let defaults = defaults.collect().unwrap();
+    let mut settings: Vec<_> = SETTINGS.read().unwrap()
+        .collect()
+        .map_err(|error| vec![error.to_string()])?
+        .into_iter()
+        .collect();
+    settings.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+    let mut errors = Vec::new();
+    for (key, value) in settings {
+        let name = format!("MIR_DUMP_{}", key.to_uppercase());
+        let text = value.clone().into_str().unwrap_or_default();
+        let default = match defaults.get(&key) {
+            Some(default) => default.clone().into_str().unwrap_or_default(),
+            // `MIR_DUMP_CONFIG` names the configuration file.
+            None if key == "config" => continue,
+            None => {
+                errors.push(format!("{}: unknown setting", name));
+                continue;
+            }
+        };
+        if default == "true" || default == "false" {
+            if value.into_bool().is_err() {
+                errors.push(format!("{}: expected bool, got '{}'", name, text));
+            }
+        } else if default.parse::<u64>().is_ok() {
+            if text.trim().parse::<u64>().is_err() {
+                errors.push(format!("{}: expected a non-negative integer, got '{}'", name, text));
+            }
+        }
+    }
+    // The settings that are not plain booleans or integers.
+    let parse_results = vec![
+        ("DUMP_MIR_PHASE", parse_dump_mir_phase().map(|_| ())),
+        ("POLONIUS_ALGORITHM", parse_polonius_algorithm().map(|_| ())),
+        ("DUMP_NODE_STYLE", parse_dump_node_style().map(|_| ())),
+        ("DUMP_GRAPH_DIRECTION", parse_dump_graph_direction().map(|_| ())),
+        ("DUMP_NODE_ID_FORMAT", parse_dump_node_id_format().map(|_| ())),
+        ("DUMP_EDGE_STYLES", parse_dump_edge_styles().map(|_| ())),
+        ("DUMP_TYPE_PRECISION", parse_dump_type_precision().map(|_| ())),
+        ("DUMP_OUTPUT_FORMATS", parse_dump_output_formats().map(|_| ())),
+        ("DUMP_STORAGE_DISPLAY", parse_dump_storage_display().map(|_| ())),
+        ("DUMP_INIT_DISPLAY", parse_dump_init_display().map(|_| ())),
+        ("DUMP_MIR_PROC_REGEX", parse_dump_mir_proc_regex().map(|_| ())),
+    ];
+    for (key, result) in parse_results {
+        if let Err(error) = result {
+            errors.push(format!("MIR_DUMP_{}: {}", key, error));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// Generate a dump of the settings
 pub fn dump() -> String {
     format!("{:?}", SETTINGS.read().unwrap())
@@ -51,21 +341,657 @@ pub fn dump_mir_info() -> bool {
     SETTINGS.read().unwrap().get::<bool>("DUMP_MIR_INFO").unwrap()
 }
 
+/// Which version of MIR should be dumped?
+pub fn dump_mir_phase() -> MirPhase {
+    parse_dump_mir_phase().unwrap_or_else(|error| panic!("Invalid DUMP_MIR_PHASE: {}", error))
+}
+
+fn parse_dump_mir_phase() -> Result<MirPhase, String> {
+    let phase = SETTINGS.read().unwrap().get::<String>("DUMP_MIR_PHASE").unwrap();
+    match phase.as_str() {
+        "validated" => Ok(MirPhase::Validated),
+        "optimized" => Ok(MirPhase::Optimized),
+        _ => Err(format!("expected validated or optimized, got '{}'", phase)),
+    }
+}
+
+/// The algorithm used to compute the Polonius output facts. `Naive` is the
+/// reference implementation; `DatafrogOpt` computes the same result faster
+/// and `LocationInsensitive` is a fast approximation.
+pub fn polonius_algorithm() -> Algorithm {
+    parse_polonius_algorithm()
+        .unwrap_or_else(|error| panic!("Invalid POLONIUS_ALGORITHM: {}", error))
+}
+
+fn parse_polonius_algorithm() -> Result<Algorithm, String> {
+    let algorithm = SETTINGS.read().unwrap().get::<String>("POLONIUS_ALGORITHM").unwrap();
+    match algorithm.as_str() {
+        "Naive" => Ok(Algorithm::Naive),
+        "DatafrogOpt" => Ok(Algorithm::DatafrogOpt),
+        "LocationInsensitive" => Ok(Algorithm::LocationInsensitive),
+        _ => Err(format!("expected Naive, DatafrogOpt, or LocationInsensitive, got '{}'",
+                         algorithm)),
+    }
+}
+
+/// Should the borrow checker output be computed with both the `Naive` and
+/// the `DatafrogOpt` algorithms and the dump panic if they disagree on
+/// `borrow_live_at`?
+pub fn dump_verify_algorithms() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_VERIFY_ALGORITHMS").unwrap()
+}
+
+/// Should the generated files start with a comment describing their origin?
+pub fn dump_add_file_header() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_ADD_FILE_HEADER").unwrap()
+}
+
+/// The title shown at the top of the graph.
+pub fn dump_graph_title() -> Option<String> {
+    let title = SETTINGS.read().unwrap().get::<String>("DUMP_GRAPH_TITLE").unwrap();
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// The description shown below the title of the graph.
+pub fn dump_graph_description() -> Option<String> {
+    let description = SETTINGS.read().unwrap().get::<String>("DUMP_GRAPH_DESCRIPTION").unwrap();
+    if description.is_empty() {
+        None
+    } else {
+        Some(description)
+    }
+}
+
+/// Should the title node show when and with which version the graph was
+/// generated?
+pub fn dump_show_metadata() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_METADATA").unwrap()
+}
+
+/// Which shape should be used for the basic block nodes?
+pub fn dump_node_style() -> NodeStyle {
+    parse_dump_node_style().unwrap_or_else(|error| panic!("Invalid DUMP_NODE_STYLE: {}", error))
+}
+
+fn parse_dump_node_style() -> Result<NodeStyle, String> {
+    let style = SETTINGS.read().unwrap().get::<String>("DUMP_NODE_STYLE").unwrap();
+    match style.as_str() {
+        "record" => Ok(NodeStyle::Record),
+        "box" => Ok(NodeStyle::Box),
+        "ellipse" => Ok(NodeStyle::Ellipse),
+        _ => Err(format!("expected record, box, or ellipse, got '{}'", style)),
+    }
+}
+
+/// The direction in which the graph is laid out (`TB`, `LR`, `BT`, or `RL`).
+pub fn dump_graph_direction() -> String {
+    parse_dump_graph_direction()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_GRAPH_DIRECTION: {}", error))
+}
+
+fn parse_dump_graph_direction() -> Result<String, String> {
+    let direction = SETTINGS.read().unwrap().get::<String>("DUMP_GRAPH_DIRECTION").unwrap();
+    match direction.as_str() {
+        "TB" | "LR" | "BT" | "RL" => Ok(direction),
+        _ => Err(format!("expected TB, LR, BT, or RL, got '{}'", direction)),
+    }
+}
+
+/// The font of the basic block nodes. Empty means the graphviz default.
+pub fn dump_node_fontname() -> String {
+    SETTINGS.read().unwrap().get::<String>("DUMP_NODE_FONTNAME").unwrap()
+}
+
+/// The font size of the basic block nodes. Empty means the graphviz default.
+pub fn dump_node_fontsize() -> String {
+    SETTINGS.read().unwrap().get::<String>("DUMP_NODE_FONTSIZE").unwrap()
+}
+
+/// The font size of the edge labels. Empty means the graphviz default.
+pub fn dump_edge_fontsize() -> String {
+    SETTINGS.read().unwrap().get::<String>("DUMP_EDGE_FONTSIZE").unwrap()
+}
+
+/// How should the basic block nodes be identified in the graph?
+pub fn dump_node_id_format() -> NodeIdFormat {
+    parse_dump_node_id_format()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_NODE_ID_FORMAT: {}", error))
+}
+
+fn parse_dump_node_id_format() -> Result<NodeIdFormat, String> {
+    let format = SETTINGS.read().unwrap().get::<String>("DUMP_NODE_ID_FORMAT").unwrap();
+    match format.as_str() {
+        "name" => Ok(NodeIdFormat::Name),
+        "index" => Ok(NodeIdFormat::Index),
+        "path" => Ok(NodeIdFormat::Path),
+        _ => Err(format!("expected name, index, or path, got '{}'", format)),
+    }
+}
+
+/// Should the basic blocks ending with `SwitchInt` be drawn as diamonds?
+pub fn dump_switch_diamond() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SWITCH_DIAMOND").unwrap()
+}
+
+/// Should the basic blocks of each loop be grouped into a cluster?
+pub fn dump_cluster_loops() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_CLUSTER_LOOPS").unwrap()
+}
+
+/// Should the edges be labelled with the conditions under which they are
+/// taken?
+pub fn dump_show_edge_labels() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_EDGE_LABELS").unwrap()
+}
+
+/// Graphviz attributes of the edges of each kind (`normal`, `back`,
+/// `unwind`, `imaginary`, `return`, `resume`, or `abort`). Back edges are
+/// the normal edges that close a loop. The setting is a list of
+/// `kind=attributes` entries separated by `;`, for example
+/// `unwind=color=red,penwidth=2;return=style=bold`.
+pub fn dump_edge_styles() -> HashMap<String, String> {
+    parse_dump_edge_styles().unwrap_or_else(|error| panic!("Invalid DUMP_EDGE_STYLES: {}", error))
+}
+
+fn parse_dump_edge_styles() -> Result<HashMap<String, String>, String> {
+    let styles = SETTINGS.read().unwrap().get::<String>("DUMP_EDGE_STYLES").unwrap();
+    let mut result = HashMap::new();
+    for entry in styles.split(';').map(|entry| entry.trim()).filter(|entry| !entry.is_empty()) {
+        let mut parts = entry.splitn(2, '=');
+        let kind = parts.next().unwrap().trim();
+        let attributes = match parts.next() {
+            Some(attributes) => attributes.trim(),
+            None => return Err(format!("expected kind=attributes, got '{}'", entry)),
+        };
+        match kind {
+            "normal" | "back" | "unwind" | "imaginary" | "return" | "resume" | "abort" => {}
+            _ => return Err(format!("unknown edge kind '{}'", kind)),
+        }
+        result.insert(kind.to_string(), attributes.to_string());
+    }
+    Ok(result)
+}
+
+/// Should the mir dump show the result of the borrow checker?
+pub fn dump_show_borrowck_result() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_BORROWCK_RESULT").unwrap()
+}
+
+/// Should a separate graph of the outlives constraints be written for
+/// each basic block?
+pub fn dump_animate_subset() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_ANIMATE_SUBSET").unwrap()
+}
+
+/// How detailed should the displayed types be?
+pub fn dump_type_precision() -> TypePrecision {
+    parse_dump_type_precision()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_TYPE_PRECISION: {}", error))
+}
+
+fn parse_dump_type_precision() -> Result<TypePrecision, String> {
+    let precision = SETTINGS.read().unwrap().get::<String>("DUMP_TYPE_PRECISION").unwrap();
+    match precision.as_str() {
+        "full" => Ok(TypePrecision::Full),
+        "short" => Ok(TypePrecision::Short),
+        "abbrev" => Ok(TypePrecision::Abbrev),
+        _ => Err(format!("expected full, short, or abbrev, got '{}'", precision)),
+    }
+}
+
+/// Should the types be printed as in the compiler messages instead of
+/// their `Debug` representation?
+pub fn dump_pretty_types() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_PRETTY_TYPES").unwrap()
+}
+
+/// The maximum nesting depth of the generic arguments shown in types.
+/// Deeper arguments are replaced with `...`. `0` means no limit.
+pub fn dump_max_type_depth() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MAX_TYPE_DEPTH").unwrap()
+}
+
+/// The maximum number of characters shown in a table cell. Longer
+/// contents are truncated, keeping their markup. `0` means no limit.
+pub fn dump_max_cell_chars() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MAX_CELL_CHARS").unwrap()
+}
+
+/// Should the generic substitutions of the called functions be shown in the
+/// terminators of calls?
+pub fn dump_mir_substs() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_MIR_SUBSTS").unwrap()
+}
+
+/// The maximum number of characters of the shown substitutions. Longer
+/// substitutions are truncated. `0` means no limit.
+pub fn dump_mir_substs_truncate() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MIR_SUBSTS_TRUNCATE").unwrap()
+}
+
+/// The maximum number of basic blocks in one graph file. Graphs of larger
+/// functions are split into several files. `0` means no limit.
+pub fn dump_max_bb_per_file() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MAX_BB_PER_FILE").unwrap()
+}
+
+/// Should non-ASCII characters be written as `&#NNNN;` entities?
+pub fn dump_escape_unicode() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_ESCAPE_UNICODE").unwrap()
+}
+
 /// Should the mir dump show temporary variables?
 pub fn dump_show_temp_variables() -> bool {
     SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_TEMP_VARIABLES").unwrap()
 }
 
+/// Should the variables table list the locals without a region? If not,
+/// only the locals that have a region in their type are listed.
+pub fn dump_show_all_locals() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_ALL_LOCALS").unwrap()
+}
+
 /// Should the mir dump show temporary variables?
 pub fn dump_show_statement_indices() -> bool {
     SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_STATEMENT_INDICES").unwrap()
 }
 
+/// Should the graph contain a node that lists the settings with which it
+/// was generated?
+pub fn dump_show_config_node() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_CONFIG_NODE").unwrap()
+}
+
+/// Should casts to raw pointers and borrows through raw pointers be
+/// highlighted?
+pub fn dump_highlight_raw_casts() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_HIGHLIGHT_RAW_CASTS").unwrap()
+}
+
+/// Should the header rows and the terminator row of each basic block be
+/// separated from the statement rows by horizontal rules?
+pub fn dump_highlight_bb_boundaries() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_HIGHLIGHT_BB_BOUNDARIES").unwrap()
+}
+
+/// Should each basic block start with a row that lists its predecessors?
+pub fn dump_show_predecessors() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_PREDECESSORS").unwrap()
+}
+
+/// Should the basic blocks with identical statements be collapsed into a
+/// single node?
+pub fn dump_aggregate_identical() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_AGGREGATE_IDENTICAL").unwrap()
+}
+
+/// Should the loans and regions at the start points be shown?
+pub fn dump_show_start_points() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_START_POINTS").unwrap()
+}
+
+/// Should the loans and regions at the mid points be shown?
+pub fn dump_show_mid_points() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_MID_POINTS").unwrap()
+}
+
+/// The formats in which the MIR of each function should be written. All
+/// formats are written into the same directory.
+pub fn dump_output_formats() -> Vec<OutputFormat> {
+    parse_dump_output_formats()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_OUTPUT_FORMATS: {}", error))
+}
+
+fn parse_dump_output_formats() -> Result<Vec<OutputFormat>, String> {
+    let formats = SETTINGS.read().unwrap().get::<String>("DUMP_OUTPUT_FORMATS").unwrap();
+    split_list(&formats)
+        .iter()
+        .map(|format| match format.as_str() {
+            "dot" => Ok(OutputFormat::Dot),
+            "json" => Ok(OutputFormat::Json),
+            "html" => Ok(OutputFormat::Html),
+            "text" => Ok(OutputFormat::Text),
+            _ => Err(format!("expected dot, json, html, or text, got '{}'", format)),
+        })
+        .collect()
+}
+
+/// Should the graphviz files be rendered into `.svg` files with `dot`?
+pub fn dump_output_svg() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_OUTPUT_SVG").unwrap()
+}
+
+/// Should the graphviz files be rendered into `.png` files with `dot`?
+pub fn dump_output_png() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_OUTPUT_PNG").unwrap()
+}
+
+/// Should the HTML output have navigation links between the basic blocks,
+/// that is, links to the predecessors and successors in each table and a
+/// table of contents?
+pub fn dump_show_bb_navigation() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_BB_NAVIGATION").unwrap()
+}
+
+/// The path of the template of the HTML output, or `None` to use the
+/// built-in one. The template can contain the placeholders
+/// `{{GRAPH_HTML}}` (the tables of the basic blocks), `{{GRAPH_JSON}}` (the
+/// graph as written to `graph.json`), `{{FUNCTION_NAME}}`,
+/// `{{FUNCTION_SIGNATURE}}`, `{{CRATE_NAME}}`, and `{{TIMESTAMP}}` (seconds
+/// since the Unix epoch). The "back to top" links of
+/// `DUMP_SHOW_BB_NAVIGATION` refer to the element with the id `top`.
+pub fn dump_html_template() -> Option<String> {
+    let template = SETTINGS.read().unwrap().get::<String>("DUMP_HTML_TEMPLATE").unwrap();
+    if template.is_empty() {
+        None
+    } else {
+        Some(template)
+    }
+}
+
+/// How should the `StorageLive` and `StorageDead` statements be displayed?
+pub fn dump_storage_display() -> StorageDisplay {
+    parse_dump_storage_display()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_STORAGE_DISPLAY: {}", error))
+}
+
+fn parse_dump_storage_display() -> Result<StorageDisplay, String> {
+    let display = SETTINGS.read().unwrap().get::<String>("DUMP_STORAGE_DISPLAY").unwrap();
+    match display.as_str() {
+        "hide" => Ok(StorageDisplay::Hide),
+        "show" => Ok(StorageDisplay::Show),
+        "hint" => Ok(StorageDisplay::Hint),
+        _ => Err(format!("expected hide, show, or hint, got '{}'", display)),
+    }
+}
+
+/// How should the definitely initialized places be displayed?
+pub fn dump_init_display() -> InitDisplay {
+    parse_dump_init_display()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_INIT_DISPLAY: {}", error))
+}
+
+fn parse_dump_init_display() -> Result<InitDisplay, String> {
+    let display = SETTINGS.read().unwrap().get::<String>("DUMP_INIT_DISPLAY").unwrap();
+    match display.as_str() {
+        "full" => Ok(InitDisplay::Full),
+        "diff" => Ok(InitDisplay::Diff),
+        "count" => Ok(InitDisplay::Count),
+        _ => Err(format!("expected full, diff, or count, got '{}'", display)),
+    }
+}
+
+/// Should the move paths of the definitely initialized places be shown in
+/// an additional column?
+pub fn dump_show_move_paths() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_MOVE_PATHS").unwrap()
+}
+
+/// Should the definitely uninitialized places be shown in an additional
+/// column?
+pub fn dump_show_uninitialized() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_UNINITIALIZED").unwrap()
+}
+
+/// Should the places that are initialized on at least one path be shown in
+/// an additional column?
+pub fn dump_show_maybe_initialized() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_MAYBE_INITIALIZED").unwrap()
+}
+
+/// Should the regions in the region columns be followed by the name of the
+/// variable that has them in its type?
+pub fn dump_expand_region_names() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_EXPAND_REGION_NAMES").unwrap()
+}
+
+/// The minimum number of program points at which a region has to be live
+/// to be shown in the region columns. The other regions are only counted.
+/// `1` shows all regions.
+pub fn dump_min_region_importance() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MIN_REGION_IMPORTANCE").unwrap()
+}
+
+/// The maximum number of regions shown in a cell of the Regions columns.
+/// Universal regions and regions of variables are preferred. `0` means no
+/// limit.
+pub fn dump_max_regions_per_cell() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MAX_REGIONS_PER_CELL").unwrap()
+}
+
+/// Should the universal regions be left out of the Regions columns?
+pub fn dump_hide_universal_regions() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_HIDE_UNIVERSAL_REGIONS").unwrap()
+}
+
+/// Should the Regions columns list the universal and the local regions in
+/// separate groups?
+pub fn dump_show_region_kinds() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_REGION_KINDS").unwrap()
+}
+
+/// Should the mir dump show the intervals in which the loans are alive?
+pub fn dump_show_loan_intervals() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_LOAN_INTERVALS").unwrap()
+}
+
+/// Should the mir dump show the source code that created each loan?
+pub fn dump_show_loan_sources() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_LOAN_SOURCES").unwrap()
+}
+
+/// Should the mir dump start with a table that summarizes all loans of the
+/// function?
+pub fn dump_show_loan_summary() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_LOAN_SUMMARY").unwrap()
+}
+
+/// Should the loans that die at each statement be shown in an additional
+/// column?
+pub fn dump_show_dying_loans() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_DYING_LOANS").unwrap()
+}
+
+/// Should the loans be shown together with the location that created them,
+/// for example `bw3@bb1[2]`?
+pub fn dump_show_loan_locations() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_LOAN_LOCATIONS").unwrap()
+}
+
+/// Should the loans be prefixed with the kind of the borrow that created
+/// them: `&` (shared), `&mut` (mutable), or `2φ` (two-phase)?
+pub fn dump_show_loan_kinds() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_LOAN_KINDS").unwrap()
+}
+
+/// Should the loans cells be highlighted in red when a mutable loan is live
+/// together with another loan of an overlapping place?
+pub fn dump_show_conflicting_loans() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_CONFLICTING_LOANS").unwrap()
+}
+
+/// Should the mir dump hide `AscribeUserType` statements?
+pub fn dump_hide_ascribe_user_type() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_HIDE_ASCRIBE_USER_TYPE").unwrap()
+}
+
+/// The prefix that should be stripped from the displayed source file paths.
+/// If empty, the common prefix of all source files of the crate is used.
+pub fn dump_strip_path_prefix() -> Option<String> {
+    let prefix = SETTINGS.read().unwrap().get::<String>("DUMP_STRIP_PATH_PREFIX").unwrap();
+    if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix)
+    }
+}
+
+/// Should the displayed source file paths include the directories? If not,
+/// only the file name is shown.
+pub fn dump_show_full_paths() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SHOW_FULL_PATHS").unwrap()
+}
+
+/// Should the directories of source files outside of the current directory
+/// be hidden from the displayed paths?
+pub fn dump_redact_paths() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_REDACT_PATHS").unwrap()
+}
+
+/// Should the function names be replaced with their hashes in the graphs,
+/// the summary, and the other reports? The names of the output directories
+/// are chosen by rustc and are not redacted.
+pub fn dump_redact_function_names() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_REDACT_FUNCTION_NAMES").unwrap()
+}
+
+/// Should all functions be dumped if neither `DUMP_MIR_PROC` nor
+/// `DUMP_MIR_PROC_REGEX` is set? If not, nothing is dumped unless one of
+/// them selects the functions.
+pub fn dump_all_functions() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_ALL_FUNCTIONS").unwrap()
+}
+
+/// Should closures be dumped too? They are selected by names like
+/// `outer_fn::closure_at_line_12`, where the enclosing item is qualified
+/// as for methods, and a second closure on the same line gets the suffix
+/// `_2`. Nested `fn` items are dumped regardless of this setting.
+pub fn dump_include_closures() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_INCLUDE_CLOSURES").unwrap()
+}
+
 /// The function of which MIR info should be dumped.
 pub fn dump_mir_proc() -> Option<String> {
     SETTINGS.read().unwrap().get::<Option<String>>("DUMP_MIR_PROC").unwrap()
 }
 
+/// A regular expression that matches the names of the functions of which
+/// MIR info should be dumped. Ignored if `DUMP_MIR_PROC` is set.
+pub fn dump_mir_proc_regex() -> Option<Regex> {
+    parse_dump_mir_proc_regex()
+        .unwrap_or_else(|error| panic!("Invalid DUMP_MIR_PROC_REGEX: {}", error))
+}
+
+fn parse_dump_mir_proc_regex() -> Result<Option<Regex>, String> {
+    let pattern = SETTINGS.read().unwrap().get::<String>("DUMP_MIR_PROC_REGEX").unwrap();
+    if pattern.is_empty() {
+        Ok(None)
+    } else {
+        Regex::new(&pattern).map(Some).map_err(|error| error.to_string())
+    }
+}
+
+/// Names or glob patterns of functions whose MIR info should not be dumped.
+pub fn dump_exclude_procs() -> Vec<String> {
+    split_list(&SETTINGS.read().unwrap().get::<String>("DUMP_EXCLUDE_PROCS").unwrap())
+}
+
+/// Suffixes of the names of functions whose MIR info should not be dumped.
+pub fn dump_exclude_suffixes() -> Vec<String> {
+    split_list(&SETTINGS.read().unwrap().get::<String>("DUMP_EXCLUDE_SUFFIXES").unwrap())
+}
+
+/// Prefixes of the names of functions whose MIR info should not be dumped.
+pub fn dump_exclude_prefixes() -> Vec<String> {
+    split_list(&SETTINGS.read().unwrap().get::<String>("DUMP_EXCLUDE_PREFIXES").unwrap())
+}
+
+/// Names or glob patterns of functions that should be analysed and
+/// included in the summary, but for which no files should be written.
+pub fn dump_quiet_patterns() -> Vec<String> {
+    split_list(&SETTINGS.read().unwrap().get::<String>("DUMP_QUIET_PATTERNS").unwrap())
+}
+
+/// Functions with fewer basic blocks are not dumped.
+pub fn dump_min_basic_blocks() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MIN_BASIC_BLOCKS").unwrap()
+}
+
+/// Functions with fewer loans are not dumped.
+pub fn dump_min_loan_count() -> usize {
+    SETTINGS.read().unwrap().get::<usize>("DUMP_MIN_LOAN_COUNT").unwrap()
+}
+
+/// Should the functions without any `borrow_region` facts be skipped?
+pub fn dump_skip_trivial() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_SKIP_TRIVIAL").unwrap()
+}
+
+/// Should the graph of a function without any `borrow_region` facts be
+/// replaced with a one-line stub? Ignored if `DUMP_SKIP_TRIVIAL` is set.
+pub fn dump_trivial_stub() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_TRIVIAL_STUB").unwrap()
+}
+
+/// Should the facts of the given relation (for example, `"killed"`) be
+/// loaded? If not, the relation is left empty.
+pub fn dump_load_relation(relation: &str) -> bool {
+    let key = format!("DUMP_LOAD_{}", relation.to_uppercase());
+    SETTINGS.read().unwrap().get::<bool>(&key).unwrap()
+}
+
+/// Should the edges between basic blocks in the `cfg_edge` facts be
+/// compared with the edges of the graph? Discrepancies are reported as
+/// warnings.
+pub fn dump_verify_cfg_edges() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_VERIFY_CFG_EDGES").unwrap()
+}
+
+/// Should panics that happen while dumping a function be caught and
+/// reported instead of aborting the whole run?
+pub fn dump_catch_panics() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_CATCH_PANICS").unwrap()
+}
+
+/// Should a heuristic reproduction (`repro.rs`) of each function be written
+/// next to its graph?
+pub fn dump_generate_repro() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_GENERATE_REPRO").unwrap()
+}
+
+/// Should the loans alive at each program point be written into
+/// `timeline.json` next to the graph?
+pub fn dump_export_timeline() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_EXPORT_TIMELINE").unwrap()
+}
+
+/// Should the graph of all outlives constraints be written into
+/// `region_constraints.dot` next to the graph?
+pub fn dump_export_region_constraints() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_EXPORT_REGION_CONSTRAINTS").unwrap()
+}
+
+/// Should the input facts be written as CSV files into the `facts_csv`
+/// directory next to the graph?
+pub fn dump_export_facts_csv() -> bool {
+    SETTINGS.read().unwrap().get::<bool>("DUMP_EXPORT_FACTS_CSV").unwrap()
+}
+
+/// The file to which the summary of the run should be written. An empty
+/// path disables the summary.
+pub fn dump_summary_file() -> Option<String> {
+    let path = SETTINGS.read().unwrap().get::<String>("DUMP_SUMMARY_FILE").unwrap();
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// A shell script that is sourced before the settings are read and the
+/// compiler is started. The environment variables it sets or unsets are
+/// passed to the compiler, so it can configure the logging and the other
+/// `MIR_DUMP_*` settings. For the same reason, the path is read directly
+/// from the `MIR_DUMP_DUMP_INIT_SCRIPT` environment variable and not from
+/// the configuration file. An empty path disables the script.
+pub fn dump_init_script() -> Option<String> {
+    env::var("MIR_DUMP_DUMP_INIT_SCRIPT").ok().filter(|path| !path.is_empty())
+}
+
 /// In which folder should we sore log/dumps?
 pub fn log_dir() -> String {
     SETTINGS.read().unwrap().get::<String>("LOG_DIR").unwrap()
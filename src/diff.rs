@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Comparison of two dumps of the same function.
+//!
+//! The dumps are read from the `graph.json` files written with the `json`
+//! output format. The statements of each basic block are matched with the
+//! longest common subsequence, where two statements match if they have the
+//! same text and the same facts. The result is a graph in which the
+//! unchanged rows are grey, the added rows are green, and the removed rows
+//! are red.
+
+use crate::mir_dumper::{escape_attribute, read_graph_info, BlockInfo, StatementInfo};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// How a row changed between the old and the new dump.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Change {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+impl Change {
+    fn color(self) -> &'static str {
+        match self {
+            Change::Unchanged => "lightgrey",
+            Change::Added => "palegreen",
+            Change::Removed => "lightcoral",
+        }
+    }
+}
+
+/// The columns of a statement that are compared. The location is ignored,
+/// because it changes whenever a statement is added before it.
+fn statement_key(statement: &StatementInfo) -> Vec<&Vec<String>> {
+    vec![
+        &statement.loans_start,
+        &statement.loans_mid,
+        &statement.borrow_regions_start,
+        &statement.borrow_regions_mid,
+        &statement.regions_start,
+        &statement.regions_mid,
+        &statement.initialized_after,
+    ]
+}
+
+fn same_statement(old: &StatementInfo, new: &StatementInfo) -> bool {
+    old.text == new.text && statement_key(old) == statement_key(new)
+}
+
+/// Match the old and the new statements with the longest common
+/// subsequence and list them in the order of the new block, with the
+/// removed statements placed before the next matched one.
+fn diff_statements<'a>(
+    old: &'a [StatementInfo],
+    new: &'a [StatementInfo],
+) -> Vec<(Change, &'a StatementInfo)> {
+    // `lengths[i][j]` is the length of the longest common subsequence of
+    // `old[i..]` and `new[j..]`.
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if same_statement(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut rows = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && same_statement(&old[i], &new[j]) {
+            rows.push((Change::Unchanged, &new[j]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            rows.push((Change::Removed, &old[i]));
+            i += 1;
+        } else {
+            rows.push((Change::Added, &new[j]));
+            j += 1;
+        }
+    }
+    rows
+}
+
+fn find_block<'a>(blocks: &'a [BlockInfo], name: &str) -> Option<&'a BlockInfo> {
+    blocks.iter().find(|block| block.block == name)
+}
+
+fn write_row(file: &mut dyn Write, change: Change, statement: &StatementInfo) -> io::Result<()> {
+    write!(file, "<tr><td bgcolor=\"{}\">{}</td><td bgcolor=\"{0}\">{}</td>",
+           change.color(), statement.location, escape_attribute(&statement.text))?;
+    for column in statement_key(statement) {
+        write!(file, "<td bgcolor=\"{}\">{}</td>",
+               change.color(), escape_attribute(&column.join(", ")))?;
+    }
+    writeln!(file, "</tr>")
+}
+
+fn write_block(
+    file: &mut dyn Write,
+    name: &str,
+    old: Option<&BlockInfo>,
+    new: Option<&BlockInfo>,
+) -> io::Result<()> {
+    writeln!(file, "\"{}\" [ shape = \"none\" label =<<table>", name)?;
+    writeln!(file, "<tr><td>{}</td><td>statement</td>\
+                    <td>Loans (start)</td><td>Loans (mid)</td>\
+                    <td>Borrow Regions (start)</td><td>Borrow Regions (mid)</td>\
+                    <td>Regions (start)</td><td>Regions (mid)</td>\
+                    <td>Definitely Initialized</td></tr>", name)?;
+    let old_statements = old.map(|block| &block.statements[..]).unwrap_or(&[]);
+    let new_statements = new.map(|block| &block.statements[..]).unwrap_or(&[]);
+    for (change, statement) in diff_statements(old_statements, new_statements) {
+        write_row(file, change, statement)?;
+    }
+    writeln!(file, "</table>> ];")
+}
+
+/// Compare `graph.json` in `old_dir` with `graph.json` in `new_dir` and
+/// write the differences as a graph into `output_path`.
+pub fn diff_graphs(old_dir: &Path, new_dir: &Path, output_path: &Path) -> io::Result<()> {
+    let old_graph = read_graph_info(&old_dir.join("graph.json"))?;
+    let new_graph = read_graph_info(&new_dir.join("graph.json"))?;
+    let mut file = BufWriter::new(File::create(output_path)?);
+    writeln!(file, "digraph G {{")?;
+    writeln!(file, "label = \"{}\";", new_graph.function.replace('"', "\\\""))?;
+    let mut names: Vec<_> = new_graph.blocks.iter().map(|block| block.block.clone()).collect();
+    for block in old_graph.blocks.iter() {
+        if !names.contains(&block.block) {
+            names.push(block.block.clone());
+        }
+    }
+    let mut old_edges = BTreeSet::new();
+    let mut new_edges = BTreeSet::new();
+    for name in names.iter() {
+        let old_block = find_block(&old_graph.blocks, name);
+        let new_block = find_block(&new_graph.blocks, name);
+        write_block(&mut file, name, old_block, new_block)?;
+        if let Some(block) = old_block {
+            old_edges.extend(block.successors.iter().map(|successor| (name, successor)));
+        }
+        if let Some(block) = new_block {
+            new_edges.extend(block.successors.iter().map(|successor| (name, successor)));
+        }
+    }
+    for &(source, target) in old_edges.union(&new_edges) {
+        let edge = (source, target);
+        let style = if !old_edges.contains(&edge) {
+            " [color=green]"
+        } else if !new_edges.contains(&edge) {
+            " [color=red]"
+        } else {
+            ""
+        };
+        writeln!(file, "\"{}\" -> \"{}\"{}", source, target, style)?;
+    }
+    writeln!(file, "}}")?;
+    Ok(())
+}
@@ -17,12 +17,15 @@ mod driver_utils;
 
 use crate::driver_utils::run;
 use log::{debug, trace, info};
-use mir_dump::{configuration, mir_dumper};
+use mir_dump::{configuration, diff, facts_exchange, mir_dumper};
 use rustc::session;
 use rustc_codegen_utils::codegen_backend::CodegenBackend;
 use rustc_driver::{driver, getopts, Compilation, CompilerCalls, RustcDefaultCalls};
 use syntax::ast;
+use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::Instant;
@@ -148,9 +151,143 @@ impl<'a> CompilerCalls<'a> for DumperCompilerCalls {
     }
 }
 
+/// Run the `export-facts`, `import-facts`, or `diff` command if it was
+/// requested. Returns `None` if the arguments do not start with a command.
+fn run_command(args: &[String]) -> Option<i32> {
+    let result = match args.get(1).map(|arg| arg.as_str()) {
+        Some("export-facts") if args.len() == 4 => {
+            facts_exchange::export_facts(Path::new(&args[2]), Path::new(&args[3]))
+        }
+        Some("import-facts") if args.len() == 4 => {
+            facts_exchange::import_facts(Path::new(&args[2]), Path::new(&args[3]))
+        }
+        Some("diff") if args.len() == 4 => {
+            let new_dir = Path::new(&args[3]);
+            diff::diff_graphs(Path::new(&args[2]), new_dir, &new_dir.join("graph_diff.dot"))
+        }
+        Some("export-facts") => {
+            eprintln!("Usage: {} export-facts <nll_facts_dir> <output.mir-facts>", args[0]);
+            return Some(1);
+        }
+        Some("import-facts") => {
+            eprintln!("Usage: {} import-facts <input.mir-facts> <nll_facts_dir>", args[0]);
+            return Some(1);
+        }
+        Some("diff") => {
+            eprintln!("Usage: {} diff <old_dump_dir> <new_dump_dir>", args[0]);
+            return Some(1);
+        }
+        _ => return None,
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            Some(1)
+        }
+    }
+}
+
+/// The hidden command with which `run_init_script` reads the environment
+/// that the init script leaves behind.
+const PRINT_ENV_COMMAND: &str = "__print-env";
+
+/// Write the environment of this process into the file as `KEY=value`
+/// entries separated by NUL bytes.
+fn print_env(path: &Path) -> io::Result<()> {
+    let mut environment = Vec::new();
+    for (key, value) in env::vars_os() {
+        environment.extend_from_slice(key.to_string_lossy().as_bytes());
+        environment.push(b'=');
+        environment.extend_from_slice(value.to_string_lossy().as_bytes());
+        environment.push(0);
+    }
+    fs::write(path, environment)
+}
+
+/// Source the script in a shell and copy the environment that it leaves
+/// behind into the environment of this process, including the removal of
+/// the variables it unsets. The shell runs this executable with
+/// `PRINT_ENV_COMMAND` to write the environment into a temporary file, so
+/// that the output of the script does not interfere with it. This runs
+/// before the logger is initialized, so problems are reported directly.
+fn run_init_script(script: &str) {
+    // `.` looks up a path without a slash in `PATH`, so the script is
+    // passed with an absolute path.
+    let script_path = env::current_dir().unwrap_or_default().join(script);
+    if !script_path.exists() {
+        eprintln!("warning: the init script {} does not exist", script);
+        return;
+    }
+    let current_exe = env::current_exe().expect("failed to find the driver executable");
+    let env_path = env::temp_dir().join(format!("mir-dump-env-{}", std::process::id()));
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg("set -a && . \"$0\" && exec \"$1\" \"$2\" \"$3\"")
+        .arg(&script_path)
+        .arg(&current_exe)
+        .arg(PRINT_ENV_COMMAND)
+        .arg(&env_path)
+        .status()
+        .expect("failed to run the init script");
+    if !status.success() {
+        eprintln!("error: the init script {} failed with {}", script, status);
+        std::process::exit(1);
+    }
+    let environment = fs::read(&env_path).expect("failed to read the environment of the init script");
+    let _ = fs::remove_file(&env_path);
+    let mut new_environment = HashMap::new();
+    for entry in environment.split(|&byte| byte == 0) {
+        let entry = String::from_utf8_lossy(entry);
+        let mut parts = entry.splitn(2, '=');
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            if !key.is_empty() {
+                new_environment.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    for (key, _) in env::vars_os() {
+        let key = key.to_string_lossy().to_string();
+        if !new_environment.contains_key(&key) {
+            env::remove_var(&key);
+        }
+    }
+    for (key, value) in new_environment {
+        if env::var(&key).ok().as_ref() != Some(&value) {
+            env::set_var(&key, &value);
+        }
+    }
+}
+
 pub fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 3 && args[1] == PRINT_ENV_COMMAND {
+        if let Err(error) = print_env(Path::new(&args[2])) {
+            eprintln!("error: {}", error);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // The init script may configure the logging and the `MIR_DUMP_*`
+    // settings, so it runs before they are read.
+    if let Some(script) = configuration::dump_init_script() {
+        run_init_script(&script);
+    }
+
     env_logger::init();
 
+    if let Err(errors) = configuration::validate() {
+        for error in errors {
+            eprintln!("error: {}", error);
+        }
+        std::process::exit(1);
+    }
+
+    if let Some(exit_status) = run_command(&args) {
+        std::process::exit(exit_status);
+    }
+
     let exit_status = run(move || {
         let mut args: Vec<String> = env::args().collect();
 
@@ -175,7 +312,6 @@ pub fn main() {
         };
 
         // Arguments required by dumper (Rustc may produce different MIR)
-        env::set_var("POLONIUS_ALGORITHM", "Naive");
         args.push("-Zborrowck=mir".to_owned());
         args.push("-Zpolonius".to_owned());
         args.push("-Znll-facts".to_owned());
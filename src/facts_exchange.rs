@@ -0,0 +1,268 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Conversion between the `nll-facts` directories written by rustc and a
+//! versioned text format that does not depend on the rustc version.
+//!
+//! The file starts with the line `mir-dump-facts <version>`. Each relation
+//! is introduced by a line `[<relation>] <column kinds>` and followed by
+//! one tab-separated fact per line. Atoms are written as `r<N>` (regions),
+//! `l<N>` (loans), and `bb<N>[<M>]:Start` or `bb<N>[<M>]:Mid` (points).
+//! Sections of unknown relations are skipped when importing, so that files
+//! written by newer versions can still be read.
+//!
+//! The input facts of a function, including the fake ones added by
+//! `PoloniusInfo`, can also be exported as one CSV file per relation for
+//! analysis in other tools.
+
+use crate::borrowck::facts;
+use crate::polonius_info::PoloniusInfo;
+use csv::{QuoteStyle, ReaderBuilder, WriterBuilder};
+use log::{debug, warn};
+use polonius_engine::Atom;
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::iter;
+use std::path::Path;
+
+/// The version of the format written by `export_facts`.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AtomKind {
+    Region,
+    Loan,
+    Point,
+}
+
+impl AtomKind {
+    fn name(self) -> &'static str {
+        match self {
+            AtomKind::Region => "region",
+            AtomKind::Loan => "loan",
+            AtomKind::Point => "point",
+        }
+    }
+}
+
+/// The relations that are exchanged and the kinds of their columns.
+const RELATIONS: &[(&str, &[AtomKind])] = &[
+    ("borrow_region", &[AtomKind::Region, AtomKind::Loan, AtomKind::Point]),
+    ("universal_region", &[AtomKind::Region]),
+    ("cfg_edge", &[AtomKind::Point, AtomKind::Point]),
+    ("killed", &[AtomKind::Loan, AtomKind::Point]),
+    ("outlives", &[AtomKind::Region, AtomKind::Region, AtomKind::Point]),
+    ("region_live_at", &[AtomKind::Region, AtomKind::Point]),
+    ("invalidates", &[AtomKind::Point, AtomKind::Loan]),
+];
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Convert an atom as written by rustc into the stable notation.
+fn atom_to_stable(kind: AtomKind, atom: &str) -> io::Result<String> {
+    let (pattern, replacement) = match kind {
+        AtomKind::Region => (r"^'_#(?P<id>\d+)r$", "r$id"),
+        AtomKind::Loan => (r"^bw(?P<id>\d+)$", "l$id"),
+        AtomKind::Point => (
+            r"^(?P<type>Mid|Start)\(bb(?P<bb>\d+)\[(?P<stmt>\d+)\]\)$",
+            "bb$bb[$stmt]:$type",
+        ),
+    };
+    let regex = Regex::new(pattern).unwrap();
+    if !regex.is_match(atom) {
+        return Err(invalid_data(format!("Invalid {}: {}", kind.name(), atom)));
+    }
+    Ok(regex.replace(atom, replacement).to_string())
+}
+
+/// Convert an atom in the stable notation into the form written by rustc.
+fn atom_from_stable(kind: AtomKind, atom: &str) -> io::Result<String> {
+    let (pattern, replacement) = match kind {
+        AtomKind::Region => (r"^r(?P<id>\d+)$", "'_#${id}r"),
+        AtomKind::Loan => (r"^l(?P<id>\d+)$", "bw$id"),
+        AtomKind::Point => (
+            r"^bb(?P<bb>\d+)\[(?P<stmt>\d+)\]:(?P<type>Mid|Start)$",
+            "$type(bb$bb[$stmt])",
+        ),
+    };
+    let regex = Regex::new(pattern).unwrap();
+    if !regex.is_match(atom) {
+        return Err(invalid_data(format!("Invalid {}: {}", kind.name(), atom)));
+    }
+    Ok(regex.replace(atom, replacement).to_string())
+}
+
+/// Write the facts from the `nll-facts` directory of a function into a
+/// single file in the stable format.
+pub fn export_facts(facts_dir: &Path, output_path: &Path) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(output_path)?);
+    writeln!(output, "mir-dump-facts {}", FORMAT_VERSION)?;
+    for &(relation, columns) in RELATIONS {
+        let column_names: Vec<_> = columns.iter().map(|kind| kind.name()).collect();
+        writeln!(output, "[{}] {}", relation, column_names.join(" "))?;
+        let facts_path = facts_dir.join(format!("{}.facts", relation));
+        if !facts_path.exists() {
+            debug!("Skipping missing relation: {:?}", facts_path);
+            continue;
+        }
+        let mut reader = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .from_path(facts_path)?;
+        for row in reader.records() {
+            let row = row?;
+            if row.len() != columns.len() {
+                return Err(invalid_data(format!(
+                    "Expected {} columns in {}, found {}", columns.len(), relation, row.len())));
+            }
+            let atoms = row.iter()
+                .zip(columns.iter())
+                .map(|(atom, &kind)| atom_to_stable(kind, atom))
+                .collect::<io::Result<Vec<_>>>()?;
+            writeln!(output, "{}", atoms.join("\t"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the facts from a file in the stable format into a `nll-facts`
+/// directory that can be read by the fact loader.
+pub fn import_facts(input_path: &Path, facts_dir: &Path) -> io::Result<()> {
+    let mut lines = BufReader::new(File::open(input_path)?).lines();
+    let header = lines.next().unwrap_or(Ok(String::new()))?;
+    let version: u32 = header.trim()
+        .trim_start_matches("mir-dump-facts ")
+        .parse()
+        .map_err(|_| invalid_data(format!("Invalid header: {}", header)))?;
+    if version > FORMAT_VERSION {
+        return Err(invalid_data(format!(
+            "Unsupported format version {} (the newest supported is {})",
+            version, FORMAT_VERSION)));
+    }
+    fs::create_dir_all(facts_dir)?;
+
+    let section_regex = Regex::new(r"^\[(?P<relation>\w+)\]").unwrap();
+    let mut current: Option<(&[AtomKind], csv::Writer<File>)> = None;
+    let mut skipping = false;
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(caps) = section_regex.captures(&line) {
+            if let Some((_, mut writer)) = current.take() {
+                writer.flush()?;
+            }
+            let relation = &caps["relation"];
+            match RELATIONS.iter().find(|&&(name, _)| name == relation) {
+                Some(&(_, columns)) => {
+                    let writer = WriterBuilder::new()
+                        .delimiter(b'\t')
+                        .quote_style(QuoteStyle::Always)
+                        .from_path(facts_dir.join(format!("{}.facts", relation)))?;
+                    current = Some((columns, writer));
+                    skipping = false;
+                }
+                None => {
+                    warn!("Skipping unknown relation: {}", relation);
+                    skipping = true;
+                }
+            }
+            continue;
+        }
+        if skipping {
+            continue;
+        }
+        let (columns, writer) = match current {
+            Some((columns, ref mut writer)) => (columns, writer),
+            None => return Err(invalid_data(format!("Fact outside of a relation: {}", line))),
+        };
+        let atoms: Vec<_> = line.split('\t').collect();
+        if atoms.len() != columns.len() {
+            return Err(invalid_data(format!("Expected {} columns: {}", columns.len(), line)));
+        }
+        let atoms = atoms.iter()
+            .zip(columns.iter())
+            .map(|(atom, &kind)| atom_from_stable(kind, atom))
+            .collect::<io::Result<Vec<_>>>()?;
+        writer.write_record(&atoms)?;
+    }
+    if let Some((_, mut writer)) = current.take() {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// The names of the columns of a relation in the CSV export. Columns of the
+/// same kind are numbered, for example `region1` and `region2`.
+fn csv_column_names(columns: &[AtomKind]) -> Vec<String> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(index, &kind)| {
+            if columns.iter().filter(|&&other| other == kind).count() > 1 {
+                let number = columns[..=index].iter().filter(|&&other| other == kind).count();
+                format!("{}{}", kind.name(), number)
+            } else {
+                kind.name().to_string()
+            }
+        })
+        .collect()
+}
+
+/// Write the input facts of a function into `<relation>.csv` files in
+/// `dir`. Each file has a header row and starts with a `function` column,
+/// so that the exports of several functions can be concatenated. The atoms
+/// are written in the stable notation.
+pub fn export_facts_to_csv(info: &PoloniusInfo, function: &str, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let facts = &info.borrowck_in_facts;
+    let region = |region: facts::Region| format!("r{}", region.index());
+    let loan = |loan: facts::Loan| format!("l{}", loan.index());
+    let point = |point: facts::PointIndex| info.interner.get_point(point).to_string();
+    for &(relation, columns) in RELATIONS {
+        let rows: Vec<Vec<String>> = match relation {
+            "borrow_region" => facts.borrow_region
+                .iter()
+                .map(|&(r, l, p)| vec![region(r), loan(l), point(p)])
+                .collect(),
+            "universal_region" => facts.universal_region
+                .iter()
+                .map(|&r| vec![region(r)])
+                .collect(),
+            "cfg_edge" => facts.cfg_edge
+                .iter()
+                .map(|&(p1, p2)| vec![point(p1), point(p2)])
+                .collect(),
+            "killed" => facts.killed
+                .iter()
+                .map(|&(l, p)| vec![loan(l), point(p)])
+                .collect(),
+            "outlives" => facts.outlives
+                .iter()
+                .map(|&(r1, r2, p)| vec![region(r1), region(r2), point(p)])
+                .collect(),
+            "region_live_at" => facts.region_live_at
+                .iter()
+                .map(|&(r, p)| vec![region(r), point(p)])
+                .collect(),
+            "invalidates" => facts.invalidates
+                .iter()
+                .map(|&(p, l)| vec![point(p), loan(l)])
+                .collect(),
+            _ => unreachable!("Unknown relation: {}", relation),
+        };
+        let mut writer = WriterBuilder::new().from_path(dir.join(format!("{}.csv", relation)))?;
+        let header = iter::once(String::from("function")).chain(csv_column_names(columns));
+        writer.write_record(header)?;
+        for row in rows {
+            writer.write_record(iter::once(function.to_string()).chain(row))?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
@@ -10,12 +10,15 @@ extern crate datafrog;
 extern crate rustc;
 extern crate rustc_hash;
 extern crate rustc_driver;
+extern crate rustc_mir;
 extern crate syntax;
 extern crate syntax_pos;
 extern crate rustc_data_structures;
 
 pub mod configuration;
+pub mod diff;
+pub mod facts_exchange;
 pub mod mir_dumper;
 mod mir_analyses;
-mod polonius_info;
-mod borrowck;
+pub mod polonius_info;
+pub mod borrowck;
@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module provides a lightweight analysis of local aliases.
+//!
+//! After `_3 = _1` (or `_3 = move _1`), the local `_3` holds the same
+//! value as `_1` until one of them is assigned again or goes out of
+//! scope. The analysis tracks such equalities between locals. It is
+//! computed in a single pass over the basic blocks in reverse postorder:
+//! the aliases at the start of a block are the ones that hold at the end
+//! of all its predecessors, and blocks reached by a back edge start with
+//! no aliases.
+
+use rustc::mir;
+use std::collections::HashMap;
+
+/// A map from a local to the local whose value it holds.
+pub type Aliases = HashMap<mir::Local, mir::Local>;
+
+pub struct AliasAnalysisResult {
+    /// The aliases that hold before the statement (or the terminator).
+    before_statement: HashMap<mir::Location, Aliases>,
+}
+
+impl AliasAnalysisResult {
+    /// Get the aliases that hold before the statement at `location`.
+    pub fn get_before_statement(&self, location: mir::Location) -> Option<&Aliases> {
+        self.before_statement.get(&location)
+    }
+
+    /// Group the locals that alias each other at some program point. Each
+    /// group starts with the local whose value is shared.
+    pub fn get_alias_groups(&self) -> Vec<Vec<mir::Local>> {
+        let mut groups: HashMap<mir::Local, Vec<mir::Local>> = HashMap::new();
+        for aliases in self.before_statement.values() {
+            for (&alias, &original) in aliases.iter() {
+                let group = groups.entry(original).or_insert_with(|| vec![original]);
+                if !group.contains(&alias) {
+                    group.push(alias);
+                }
+            }
+        }
+        let mut groups: Vec<_> = groups
+            .into_iter()
+            .map(|(_, mut group)| {
+                group[1..].sort();
+                group
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+}
+
+/// Remove all aliases that involve the local.
+fn kill(aliases: &mut Aliases, local: mir::Local) {
+    aliases.remove(&local);
+    aliases.retain(|_, original| *original != local);
+}
+
+/// Apply the effects of the statement on the aliases.
+fn apply_statement_effects(aliases: &mut Aliases, statement: &mir::Statement) {
+    match statement.kind {
+        mir::StatementKind::Assign(mir::Place::Local(target), ref rvalue) => {
+            kill(aliases, target);
+            match rvalue.as_ref() {
+                mir::Rvalue::Use(mir::Operand::Copy(mir::Place::Local(source))) |
+                mir::Rvalue::Use(mir::Operand::Move(mir::Place::Local(source))) => {
+                    if *source != target {
+                        let original = aliases.get(source).cloned().unwrap_or(*source);
+                        aliases.insert(target, original);
+                    }
+                }
+                _ => {}
+            }
+        }
+        mir::StatementKind::StorageLive(local) |
+        mir::StatementKind::StorageDead(local) => {
+            kill(aliases, local);
+        }
+        _ => {}
+    }
+}
+
+/// Apply the effects of the terminator on the aliases.
+fn apply_terminator_effects(aliases: &mut Aliases, terminator: &mir::Terminator) {
+    match terminator.kind {
+        mir::TerminatorKind::Call {
+            destination: Some((mir::Place::Local(target), _)),
+            ..
+        } |
+        mir::TerminatorKind::DropAndReplace {
+            location: mir::Place::Local(target),
+            ..
+        } => {
+            kill(aliases, target);
+        }
+        _ => {}
+    }
+}
+
+/// Compute which locals alias each other at each program point.
+pub fn compute_aliases<'tcx>(mir: &mir::Mir<'tcx>) -> AliasAnalysisResult {
+    let mut before_statement = HashMap::new();
+    let mut after_block: HashMap<mir::BasicBlock, Aliases> = HashMap::new();
+    for (bb, data) in mir::traversal::reverse_postorder(mir) {
+        let predecessors = mir.predecessors_for(bb);
+        let mut aliases = if predecessors.iter().all(|pred| after_block.contains_key(pred)) {
+            let mut incoming = predecessors.iter().map(|pred| &after_block[pred]);
+            match incoming.next() {
+                Some(first) => {
+                    let mut aliases = first.clone();
+                    for other in incoming {
+                        aliases.retain(|alias, original| other.get(alias) == Some(original));
+                    }
+                    aliases
+                }
+                None => Aliases::new(),
+            }
+        } else {
+            Aliases::new()
+        };
+        let mut location = mir::Location { block: bb, statement_index: 0 };
+        for statement in data.statements.iter() {
+            before_statement.insert(location, aliases.clone());
+            apply_statement_effects(&mut aliases, statement);
+            location.statement_index += 1;
+        }
+        before_statement.insert(location, aliases.clone());
+        if let Some(ref terminator) = data.terminator {
+            apply_terminator_effects(&mut aliases, terminator);
+        }
+        after_block.insert(bb, aliases);
+    }
+    AliasAnalysisResult {
+        before_statement: before_statement,
+    }
+}
@@ -2,7 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-//! This module provides the definitely initialized analysis for MIR.
+//! This module provides the definitely initialized, the maybe initialized,
+//! and the definitely uninitialized analyses for MIR.
 //!
 //!
 //! Definitely initialized:
@@ -13,6 +14,21 @@
 //! this set is that we never have a node and any of its descendents in
 //! the set at the same time. For example, having `x.f` and `x.f.g` in
 //! `S` at the same time is illegal.
+//!
+//! Maybe initialized:
+//!
+//! The working set has the same meaning as for the definitely initialized
+//! analysis, but the sets coming from different branches are unioned
+//! instead of intersected. Therefore, `S` contains the paths that are
+//! initialized on at least one path to the program point.
+//!
+//! Definitely uninitialized:
+//!
+//! The dual analysis uses the same working set with the same invariant,
+//! but `S` contains the paths whose leaves are definitely uninitialized.
+//! An assignment removes the target from `S` and a move or a drop adds the
+//! moved or dropped place to it. At the entry point, all locals except the
+//! arguments are definitely uninitialized.
 
 use csv::{ReaderBuilder, WriterBuilder};
 use log::trace;
@@ -27,6 +43,21 @@ use serde_derive::{Serialize, Deserialize};
 /// The result of the definitely initialized analysis.
 pub type DefinitelyInitializedAnalysisResult<'tcx> = common::AnalysisResult<PlaceSet<'tcx>>;
 
+/// The result of the maybe initialized analysis.
+pub type MaybeInitializedAnalysisResult<'tcx> = common::AnalysisResult<PlaceSet<'tcx>>;
+
+/// The result of the definitely uninitialized analysis.
+pub type DefinitelyUninitializedAnalysisResult<'tcx> = common::AnalysisResult<PlaceSet<'tcx>>;
+
+/// Which places are tracked in the place sets?
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TrackedPlaces {
+    /// The definitely initialized ones.
+    Initialized,
+    /// The definitely uninitialized ones.
+    Uninitialized,
+}
+
 /// How place sets comming from different branches should be joined?
 #[derive(Clone, Copy, Debug)]
 enum JoinOperation {
@@ -50,20 +81,27 @@ struct DefinitelyInitializedAnalysis<'a, 'tcx: 'a> {
     /// too small definitely initialized sets. See `_test2` in
     /// `/tests/verify/pass/initialization/enums.rs`.
     join_operation: JoinOperation,
+    tracked_places: TrackedPlaces,
 }
 
 impl<'a, 'tcx: 'a> DefinitelyInitializedAnalysis<'a, 'tcx> {
-    fn new(mir: &'a mir::Mir<'tcx>, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+    fn new(
+        mir: &'a mir::Mir<'tcx>,
+        tcx: TyCtxt<'a, 'tcx, 'tcx>,
+        tracked_places: TrackedPlaces,
+    ) -> Self {
         Self {
             result: DefinitelyInitializedAnalysisResult::new(),
             mir: mir,
             tcx: tcx,
             queue: Vec::new(),
             join_operation: JoinOperation::Intersect,
+            tracked_places: tracked_places,
         }
     }
     /// Initialize all places to empty sets and mark that the arguments
-    /// are definitely initialized at the entry point.
+    /// are definitely initialized at the entry point (and all other locals
+    /// are definitely uninitialized).
     fn initialize(&mut self) {
         for bb in self.mir.basic_blocks().indices() {
             self.result.before_block.insert(bb, PlaceSet::new());
@@ -80,6 +118,11 @@ impl<'a, 'tcx: 'a> DefinitelyInitializedAnalysis<'a, 'tcx> {
         }
         // Arguments are definitely initialized.
         let mut place_set = PlaceSet::new();
+        if self.tracked_places == TrackedPlaces::Uninitialized {
+            for local in self.mir.local_decls.indices() {
+                self.set_place_uninitialised(&mut place_set, &mir::Place::Local(local));
+            }
+        }
         for arg in self.mir.args_iter() {
             self.set_place_initialised(&mut place_set, &mir::Place::Local(arg));
         }
@@ -336,11 +379,17 @@ impl<'a, 'tcx: 'a> DefinitelyInitializedAnalysis<'a, 'tcx> {
     }
     /// Set `place` as definitely initialized.
     fn set_place_initialised(&self, place_set: &mut PlaceSet<'tcx>, place: &mir::Place<'tcx>) {
-        place_set.insert(place, self.mir, self.tcx);
+        match self.tracked_places {
+            TrackedPlaces::Initialized => place_set.insert(place, self.mir, self.tcx),
+            TrackedPlaces::Uninitialized => place_set.remove(place, self.mir, self.tcx),
+        }
     }
     /// Set `place` as uninitialized.
     fn set_place_uninitialised(&self, place_set: &mut PlaceSet<'tcx>, place: &mir::Place<'tcx>) {
-        place_set.remove(place, self.mir, self.tcx);
+        match self.tracked_places {
+            TrackedPlaces::Initialized => place_set.remove(place, self.mir, self.tcx),
+            TrackedPlaces::Uninitialized => place_set.insert(place, self.mir, self.tcx),
+        }
     }
 }
 
@@ -351,7 +400,7 @@ pub fn compute_definitely_initialized<'a, 'tcx: 'a>(
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
     def_path: hir::map::DefPath,
 ) -> DefinitelyInitializedAnalysisResult<'tcx> {
-    let mut analysis = DefinitelyInitializedAnalysis::new(mir, tcx);
+    let mut analysis = DefinitelyInitializedAnalysis::new(mir, tcx, TrackedPlaces::Initialized);
     analysis.initialize();
     analysis.propagate_work_queue();
     analysis.run(JoinOperation::Union);
@@ -365,6 +414,34 @@ pub fn compute_definitely_initialized<'a, 'tcx: 'a>(
     analysis.result
 }
 
+/// Compute which places are initialized on at least one path to each
+/// program point.
+pub fn compute_maybe_initialized<'a, 'tcx: 'a>(
+    mir: &'a mir::Mir<'tcx>,
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+) -> MaybeInitializedAnalysisResult<'tcx> {
+    let mut analysis = DefinitelyInitializedAnalysis::new(mir, tcx, TrackedPlaces::Initialized);
+    analysis.initialize();
+    analysis.propagate_work_queue();
+    analysis.run(JoinOperation::Union);
+    analysis.result
+}
+
+/// Compute which places are definitely uninitialized at each program
+/// point.
+pub fn compute_definitely_uninitialized<'a, 'tcx: 'a>(
+    mir: &'a mir::Mir<'tcx>,
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+) -> DefinitelyUninitializedAnalysisResult<'tcx> {
+    let mut analysis = DefinitelyInitializedAnalysis::new(mir, tcx, TrackedPlaces::Uninitialized);
+    analysis.initialize();
+    analysis.propagate_work_queue();
+    analysis.run(JoinOperation::Union);
+    analysis.propagate_work_queue();
+    analysis.run(JoinOperation::Intersect);
+    analysis.result
+}
+
 #[derive(Debug, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq)]
 /// A record for serializing definitely initialized info into a file for testing.
 struct InitializationRecord {
@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module provides the detection of natural loops in MIR.
+//!
+//! A back edge is an edge whose target dominates its source. The target
+//! of a back edge is a loop head and the loop body consists of the head
+//! and all blocks from which the source of the back edge can be reached
+//! without passing through the head.
+
+use log::trace;
+use rustc::mir;
+use std::collections::{HashMap, HashSet};
+
+/// Natural loops of a procedure.
+pub struct ProcedureLoops {
+    /// Loop heads sorted by their basic block index.
+    pub loop_heads: Vec<mir::BasicBlock>,
+    /// The basic blocks of the loop with the given head.
+    pub loop_bodies: HashMap<mir::BasicBlock, HashSet<mir::BasicBlock>>,
+    /// Edges whose target dominates their source.
+    pub back_edges: HashSet<(mir::BasicBlock, mir::BasicBlock)>,
+}
+
+impl ProcedureLoops {
+    pub fn new<'tcx>(mir: &mir::Mir<'tcx>) -> Self {
+        trace!("[enter] ProcedureLoops::new");
+        let dominators = mir.dominators();
+
+        let mut back_edges = HashSet::new();
+        for bb in mir.basic_blocks().indices() {
+            if let Some(ref terminator) = mir[bb].terminator {
+                for &successor in terminator.successors() {
+                    if dominators.is_dominated_by(bb, successor) {
+                        back_edges.insert((bb, successor));
+                    }
+                }
+            }
+        }
+
+        let mut loop_bodies = HashMap::new();
+        for &(source, head) in back_edges.iter() {
+            let body = loop_bodies.entry(head).or_insert_with(|| {
+                let mut body = HashSet::new();
+                body.insert(head);
+                body
+            });
+            let mut work_list = vec![source];
+            while let Some(bb) = work_list.pop() {
+                if body.insert(bb) {
+                    work_list.extend(mir.predecessors_for(bb).iter().cloned());
+                }
+            }
+        }
+
+        let mut loop_heads: Vec<_> = loop_bodies.keys().cloned().collect();
+        loop_heads.sort();
+        trace!("[exit] ProcedureLoops::new loop_heads={:?}", loop_heads);
+        Self {
+            loop_heads: loop_heads,
+            loop_bodies: loop_bodies,
+            back_edges: back_edges,
+        }
+    }
+
+    /// The number of loops that contain the loop with the given head,
+    /// including the loop itself.
+    pub fn get_loop_depth(&self, loop_head: mir::BasicBlock) -> usize {
+        self.get_enclosing_loop_heads(loop_head).len()
+    }
+
+    /// The heads of all loops whose body contains the basic block.
+    pub fn get_enclosing_loop_heads(&self, bb: mir::BasicBlock) -> Vec<mir::BasicBlock> {
+        self.loop_heads
+            .iter()
+            .filter(|head| self.loop_bodies[head].contains(&bb))
+            .cloned()
+            .collect()
+    }
+
+    /// The head of the innermost loop that contains the basic block.
+    pub fn get_innermost_loop_head(&self, bb: mir::BasicBlock) -> Option<mir::BasicBlock> {
+        self.get_enclosing_loop_heads(bb)
+            .into_iter()
+            .max_by_key(|&head| self.get_loop_depth(head))
+    }
+}
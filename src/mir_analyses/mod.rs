@@ -5,7 +5,10 @@
 //! This module defines various MIR analyses.
 
 
+pub mod aliases;
 pub mod common;
 pub mod initialization;
-mod place_set;
-mod utils;
+pub mod loops;
+pub(crate) mod place_set;
+pub mod soundness_checks;
+pub mod utils;
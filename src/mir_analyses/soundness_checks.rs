@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module provides simple syntactic checks of suspicious patterns in
+//! MIR that rustc should reject. They are used to explain the rejected
+//! patterns in the graph.
+
+use rustc::mir;
+
+/// A local that is moved after it was dropped.
+#[derive(Clone, Debug)]
+pub struct UseAfterDrop {
+    pub local: mir::Local,
+    /// The location of the `Drop` terminator.
+    pub drop_location: mir::Location,
+    /// The location of the assignment that moves out of the local.
+    pub move_location: mir::Location,
+}
+
+/// Does the rvalue move out of the local?
+fn moves_local(rvalue: &mir::Rvalue, local: mir::Local) -> bool {
+    let is_move = |operand: &mir::Operand| {
+        match operand {
+            mir::Operand::Move(mir::Place::Local(moved)) => *moved == local,
+            _ => false,
+        }
+    };
+    match rvalue {
+        mir::Rvalue::Use(operand) |
+        mir::Rvalue::Repeat(operand, _) |
+        mir::Rvalue::Cast(_, operand, _) |
+        mir::Rvalue::UnaryOp(_, operand) => is_move(operand),
+        mir::Rvalue::BinaryOp(_, left, right) |
+        mir::Rvalue::CheckedBinaryOp(_, left, right) => is_move(left) || is_move(right),
+        mir::Rvalue::Aggregate(_, operands) => operands.iter().any(is_move),
+        _ => false,
+    }
+}
+
+/// Find locals that are dropped by a `Drop` terminator and then moved by
+/// an assignment in the target block before they are assigned again.
+pub fn find_uses_after_drop(mir: &mir::Mir) -> Vec<UseAfterDrop> {
+    let mut result = Vec::new();
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        let (local, target) = match data.terminator().kind {
+            mir::TerminatorKind::Drop {
+                location: mir::Place::Local(local),
+                target,
+                ..
+            } => (local, target),
+            _ => continue,
+        };
+        let drop_location = mir.terminator_loc(bb);
+        for (statement_index, statement) in mir[target].statements.iter().enumerate() {
+            if let mir::StatementKind::Assign(ref place, ref rvalue) = statement.kind {
+                if moves_local(rvalue, local) {
+                    result.push(UseAfterDrop {
+                        local: local,
+                        drop_location: drop_location,
+                        move_location: mir::Location {
+                            block: target,
+                            statement_index: statement_index,
+                        },
+                    });
+                }
+                if *place == mir::Place::Local(local) {
+                    break;
+                }
+            }
+        }
+    }
+    result
+}
@@ -21,17 +21,27 @@ pub fn is_prefix(place: &mir::Place, potential_prefix: &mir::Place) -> bool {
     } else {
         match place {
             mir::Place::Local(_) |
-            mir::Place::Static(_) => false,
+            mir::Place::Static(_) |
+            mir::Place::Promoted(_) => false,
             mir::Place::Projection(box mir::Projection { base, .. }) => {
                 is_prefix(base, potential_prefix)
             }
-            mir::Place::Promoted(_) => {
-                unimplemented!();
-            }
         }
     }
 }
 
+/// Get the type of the place by walking its projection chain from the
+/// type of the base local. For example, if `_3: &T` and `T` has a field
+/// `f: u32`, the type of `(*_3).f` is `u32`. A downcast to an enum
+/// variant has the type of the enum.
+pub fn get_place_type<'a, 'tcx: 'a>(
+    place: &mir::Place<'tcx>,
+    mir: &mir::Mir<'tcx>,
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+) -> ty::Ty<'tcx> {
+    place.ty(mir, tcx).to_ty(tcx)
+}
+
 /// Expands a place `x.f.g` of type struct into a vector of places for
 /// each of the struct's fields `{x.f.g.f, x.f.g.g, x.f.g.h}`. If
 /// `without_field` is not `None`, then omits that field from the final
@@ -2,160 +2,2137 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use lazy_static::lazy_static;
 use log::trace;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
 use rustc_driver::driver;
 use rustc::hir::{self, intravisit};
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
 use rustc::mir;
+use rustc::mir::visit::{PlaceContext, Visitor};
 use rustc::ty::{self, TyCtxt};
+use rustc_mir::dataflow::move_paths::{LookupResult, MoveData};
 use syntax::ast;
-use syntax_pos::Span;
+use syntax_pos::{FileName, Span};
 use std::cell;
-use std::fs::File;
+use std::cmp;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
 use std::io::{self, Write, BufWriter};
-use std::path::PathBuf;
+use std::mem;
+use std::ops::Range;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use super::borrowck::facts;
+use super::facts_exchange::export_facts_to_csv;
 use super::mir_analyses::initialization::{
     compute_definitely_initialized,
-    DefinitelyInitializedAnalysisResult
+    compute_definitely_uninitialized,
+    compute_maybe_initialized,
+    DefinitelyInitializedAnalysisResult,
+    DefinitelyUninitializedAnalysisResult,
+    MaybeInitializedAnalysisResult
 };
-use crate::polonius_info::PoloniusInfo;
-use crate::configuration;
+use super::mir_analyses::aliases::{compute_aliases, Aliases, AliasAnalysisResult};
+use super::mir_analyses::loops::ProcedureLoops;
+use super::mir_analyses::place_set::PlaceSet;
+use super::mir_analyses::soundness_checks::{find_uses_after_drop, UseAfterDrop};
+use super::mir_analyses::utils::get_place_type;
+use crate::polonius_info::{LoanPlaces, PoloniusInfo};
+use crate::configuration::{
+    self, InitDisplay, MirPhase, NodeIdFormat, NodeStyle, OutputFormat, StorageDisplay,
+    TypePrecision
+};
+
+macro_rules! write_graph {
+    ( $self:ident, $( $x:expr ),* ) => {
+        writeln!($self.graph.borrow_mut(), "{}",
+                 encode_output(format!($( $x ),*), $self.escape_unicode))?;
+    }
+}
+
+macro_rules! escape_html {
+    ( $o:expr ) => {{
+        $o.replace("{", "\\{")
+            .replace("}", "\\}")
+            .replace("&", "&amp;")
+            .replace(">", "&gt;")
+            .replace("<", "&lt;")
+            .replace("\n", "<br/>")
+    }};
+}
+
+macro_rules! to_html {
+    ( $o:expr ) => {{
+        escape_html!(format!("{:?}", $o))
+    }};
+}
+
+macro_rules! to_dot_string {
+    ( $o:expr ) => {{
+        $o.replace("\\", "\\\\")
+            .replace("\"", "\\\"")
+            .replace("\n", "\\l")
+    }};
+}
+
+macro_rules! write_edge {
+    ( $self:ident, $source:ident, str $target:ident ) => {{
+        write_graph!($self, "\"{}\" -> \"{}\"{}\n", $self.node_id($source), stringify!($target),
+                     $self.edge_attributes(stringify!($target), None));
+    }};
+    ( $self:ident, $source:ident, unwind $target:ident ) => {{
+        write_edge!($self, $source, unwind $target, None);
+    }};
+    ( $self:ident, $source:ident, unwind $target:ident, $label:expr ) => {{
+        write_graph!($self, "\"{}\" -> {}{}\n", $self.node_id($source), $self.edge_target($target),
+                     $self.edge_attributes("unwind", $label));
+    }};
+    ( $self:ident, $source:ident, imaginary $target:ident ) => {{
+        write_edge!($self, $source, imaginary $target, None);
+    }};
+    ( $self:ident, $source:ident, imaginary $target:ident, $label:expr ) => {{
+        write_graph!($self, "\"{}\" -> {}{}\n", $self.node_id($source), $self.edge_target($target),
+                     $self.edge_attributes("imaginary", $label));
+    }};
+    ( $self:ident, $source:ident, $target:ident ) => {{
+        write_edge!($self, $source, $target, None);
+    }};
+    ( $self:ident, $source:ident, $target:ident, $label:expr ) => {{
+        write_graph!($self, "\"{}\" -> {}{}\n", $self.node_id($source), $self.edge_target($target),
+                     $self.edge_attributes($self.edge_kind($source, $target), $label));
+    }};
+}
+
+macro_rules! to_sorted_string {
+    ( $o:expr ) => {{
+        let mut vector = $o.iter().map(|x| to_html!(x)).collect::<Vec<String>>();
+        vector.sort();
+        join_truncated(vector)
+    }}
+}
+
+/// Like `to_sorted_string!`, but also returns the full list for the
+/// tooltip as described in `join_truncated_list`.
+macro_rules! to_sorted_list {
+    ( $o:expr ) => {{
+        let mut vector = $o.iter().map(|x| to_html!(x)).collect::<Vec<String>>();
+        vector.sort();
+        join_truncated_list(vector)
+    }}
+}
+
+/// Write a table cell with the given HTML content and an optional tooltip.
+/// If the content is longer than `DUMP_MAX_CELL_CHARS`, it is truncated
+/// (keeping its markup) and,
+/// unless a tooltip is given, the full content is shown as the tooltip. A
+/// `list` cell takes the result of `join_truncated_list` and shows the
+/// items summarized as `... +N more` in the tooltip.
+macro_rules! write_cell {
+    ( $self:ident, list $list:expr ) => {{
+        let (content, full_text): (String, Option<String>) = $list;
+        write_graph!($self, "{}", with_tooltip(&content, "", full_text.as_ref().map(|text| text.as_str())));
+    }};
+    ( $self:ident, $content:expr ) => {{
+        write_graph!($self, "{}", with_tooltip(&$content, "", None));
+    }};
+    ( $self:ident, $content:expr, tooltip $tooltip:expr ) => {{
+        write_graph!($self, "{}", with_tooltip(&$content, "", Some(&$tooltip)));
+    }};
+    ( $self:ident, $content:expr, $attributes:expr ) => {{
+        write_graph!($self, "{}", with_tooltip(&$content, $attributes, None));
+    }};
+    ( $self:ident, $content:expr, $attributes:expr, tooltip $tooltip:expr ) => {{
+        write_graph!($self, "{}", with_tooltip(&$content, $attributes, Some(&$tooltip)));
+    }};
+}
 
 pub fn dump_info<'r, 'a: 'r, 'tcx: 'a>(state: &'r mut driver::CompileState<'a, 'tcx>) {
     trace!("[dump_info] enter");
 
     let tcx = state.tcx.unwrap();
 
-    assert!(tcx.use_mir_borrowck(), "NLL is not enabled.");
-    let mut printer = InfoPrinter {
-        tcx: tcx,
-    };
-    intravisit::walk_crate(&mut printer, tcx.hir().krate());
+    let start = Instant::now();
+
+    assert!(tcx.use_mir_borrowck(), "NLL is not enabled.");
+    let mut printer = InfoPrinter {
+        tcx: tcx,
+        panics: Vec::new(),
+        unimplemented_functions: Vec::new(),
+        summary: Summary {
+            polonius_algorithm: format!("{:?}", configuration::polonius_algorithm()),
+            total_time_secs: 0.0,
+            functions: Vec::new(),
+            skipped: Vec::new(),
+        },
+        closure_counts: HashMap::new(),
+    };
+    intravisit::walk_crate(&mut printer, tcx.hir().krate());
+
+    if configuration::dump_catch_panics() {
+        let report_path = PathBuf::from("nll-facts").join("panics.json");
+        let report = File::create(report_path).expect("Unable to create file");
+        serde_json::to_writer_pretty(report, &printer.panics)
+            .expect("Unable to write the panics report");
+    }
+
+    if !printer.unimplemented_functions.is_empty() {
+        let report_path = PathBuf::from("nll-facts").join("unimplemented_functions.json");
+        let report = File::create(report_path).expect("Unable to create file");
+        serde_json::to_writer_pretty(report, &printer.unimplemented_functions)
+            .expect("Unable to write the unimplemented functions report");
+    }
+
+    if let Some(summary_file) = configuration::dump_summary_file() {
+        let duration = start.elapsed();
+        printer.summary.total_time_secs =
+            duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9;
+        let summary = File::create(summary_file).expect("Unable to create file");
+        serde_json::to_writer_pretty(summary, &printer.summary)
+            .expect("Unable to write the summary");
+    }
+
+    trace!("[dump_info] exit");
+}
+
+/// A function that was not dumped because it contains terminators that
+/// are not supported.
+#[derive(Debug, Serialize)]
+struct UnimplementedFunction {
+    function: String,
+    /// The unsupported terminators with the basic blocks that they end.
+    terminators: Vec<String>,
+}
+
+/// A panic that was caught while dumping a function.
+#[derive(Debug, Serialize)]
+struct PanicRecord {
+    function: String,
+    message: String,
+}
+
+/// A function whose MIR info was dumped.
+#[derive(Debug, Serialize)]
+struct FunctionSummary {
+    name: String,
+    /// The path of the graph, or `None` if the function matched
+    /// `DUMP_QUIET_PATTERNS`.
+    output_path: Option<String>,
+    /// The number of facts of each input relation.
+    fact_counts: BTreeMap<&'static str, usize>,
+}
+
+/// The loans that are alive at a program point.
+#[derive(Debug, Serialize)]
+struct TimelineEntry {
+    point: String,
+    loans: Vec<String>,
+}
+
+/// The information about a function that is written in the JSON, HTML, and
+/// text formats. It is collected once and shared by all of them. The JSON files
+/// can be read back with `read_graph_info`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphInfo {
+    /// The name of the function.
+    pub function: String,
+    pub blocks: Vec<BlockInfo>,
+}
+
+/// The rows of the table of a basic block.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockInfo {
+    /// The index of the basic block.
+    pub index: usize,
+    /// The name of the basic block, for example `bb0`.
+    pub block: String,
+    /// The definitely initialized places at the start of the block.
+    pub initialized_before: Vec<String>,
+    /// The statements followed by the terminator.
+    pub statements: Vec<StatementInfo>,
+    pub successors: Vec<String>,
+}
+
+/// The facts at the start and mid points of a statement or a terminator.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatementInfo {
+    /// The location, for example `bb0[2]`.
+    pub location: String,
+    pub text: String,
+    /// The loans alive at the start point.
+    pub loans_start: Vec<String>,
+    /// The loans alive at the mid point.
+    pub loans_mid: Vec<String>,
+    /// The `(region, loan)` pairs created at the start point.
+    pub borrow_regions_start: Vec<String>,
+    /// The `(region, loan)` pairs created at the mid point.
+    pub borrow_regions_mid: Vec<String>,
+    /// The regions alive at the start point.
+    pub regions_start: Vec<String>,
+    /// The regions alive at the mid point.
+    pub regions_mid: Vec<String>,
+    /// The definitely initialized places after the statement.
+    pub initialized_after: Vec<String>,
+}
+
+/// Writes the information shown in the tables of the DOT graph as JSON.
+pub struct JsonInfoPrinter<'a> {
+    pub graph_info: &'a GraphInfo,
+}
+
+impl<'a> JsonInfoPrinter<'a> {
+    pub fn print(&self, path: &Path) -> Result<(),io::Error> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self.graph_info)?;
+        Ok(())
+    }
+}
+
+/// Writes the information shown in the tables of the DOT graph as plain
+/// text. Each statement is a line of `|`-separated columns: the location,
+/// the statement, the loans, the borrow regions, and the regions at the
+/// start and mid points, and the definitely initialized places after it.
+/// A `|` or `\` inside a column is escaped with `\`. Empty columns are
+/// written as `-`, so that the output does not depend on trailing
+/// whitespace and can be compared with `diff`.
+pub struct TextInfoPrinter<'a> {
+    pub graph_info: &'a GraphInfo,
+}
+
+impl<'a> TextInfoPrinter<'a> {
+    pub fn print(&self, path: &Path) -> Result<(),io::Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "fn {}", self.graph_info.function)?;
+        for block in &self.graph_info.blocks {
+            writeln!(file)?;
+            writeln!(file, "{}: initialized {}", block.block,
+                     Self::column(&block.initialized_before))?;
+            for statement in &block.statements {
+                let columns = [
+                    Self::column(&statement.loans_start),
+                    Self::column(&statement.loans_mid),
+                    Self::column(&statement.borrow_regions_start),
+                    Self::column(&statement.borrow_regions_mid),
+                    Self::column(&statement.regions_start),
+                    Self::column(&statement.regions_mid),
+                    Self::column(&statement.initialized_after),
+                ];
+                writeln!(file, "{} | {} | {}", statement.location,
+                         Self::escape(&statement.text), columns.join(" | "))?;
+            }
+            writeln!(file, "{} -> {}", block.block, Self::column(&block.successors))?;
+        }
+        Ok(())
+    }
+
+    fn column(items: &[String]) -> String {
+        if items.is_empty() {
+            String::from("-")
+        } else {
+            Self::escape(&items.join(", "))
+        }
+    }
+
+    /// Escape the column separator so that the columns can be split
+    /// unambiguously.
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('|', "\\|")
+    }
+}
+
+/// Read a JSON file written by `JsonInfoPrinter`.
+pub fn read_graph_info(path: &Path) -> Result<GraphInfo, io::Error> {
+    let file = io::BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// A function whose MIR info was not dumped.
+#[derive(Debug, Serialize)]
+struct SkippedFunction {
+    name: String,
+    reason: String,
+}
+
+/// The summary of the whole run.
+#[derive(Debug, Serialize)]
+struct Summary {
+    polonius_algorithm: String,
+    total_time_secs: f64,
+    functions: Vec<FunctionSummary>,
+    skipped: Vec<SkippedFunction>,
+}
+
+struct InfoPrinter<'a, 'tcx: 'a> {
+    pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// Panics caught when `DUMP_CATCH_PANICS` is enabled.
+    pub panics: Vec<PanicRecord>,
+    /// Functions that contain terminators the dumper does not support.
+    pub unimplemented_functions: Vec<UnimplementedFunction>,
+    pub summary: Summary,
+    /// The number of closures named so far for each enclosing item and
+    /// line, used to disambiguate closures that start on the same line.
+    pub closure_counts: HashMap<(ast::NodeId, usize), usize>,
+}
+
+impl<'a, 'tcx> intravisit::Visitor<'tcx> for InfoPrinter<'a, 'tcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> intravisit::NestedVisitorMap<'this, 'tcx> {
+        let map = self.tcx.hir();
+        intravisit::NestedVisitorMap::All(map)
+    }
+
+    fn visit_fn(&mut self, fk: intravisit::FnKind<'tcx>, fd: &'tcx hir::FnDecl,
+                b: hir::BodyId, span: Span, node_id: ast::NodeId) {
+        let name = match fk {
+            intravisit::FnKind::ItemFn(name, ..) => Some(name.to_string()),
+            intravisit::FnKind::Method(..) => {
+                // The qualified name as printed by rustc, for example
+                // `<S as T>::foo` for a method of a trait implementation.
+                Some(self.tcx.item_path_str(self.tcx.hir().local_def_id(node_id)))
+            }
+            intravisit::FnKind::Closure(..) if configuration::dump_include_closures() => {
+                Some(self.closure_name(span, node_id))
+            }
+            _ => None,
+        };
+        if let Some(name) = name {
+            self.visit_named_fn(&name, node_id);
+        }
+        // Walk the body to reach the closures and the nested `fn` items,
+        // which are dumped independently of `DUMP_INCLUDE_CLOSURES`.
+        intravisit::walk_fn(self, fk, fd, b, span, node_id);
+    }
+}
+
+impl<'a, 'tcx> InfoPrinter<'a, 'tcx> {
+    /// Dump the function with the given name if it is selected by the
+    /// configuration.
+    fn visit_named_fn(&mut self, name_str: &str, node_id: ast::NodeId) {
+        let excluded = configuration::dump_exclude_procs()
+            .iter()
+            .any(|pattern| matches_glob(name_str, pattern)) ||
+            configuration::dump_exclude_suffixes()
+            .iter()
+            .any(|suffix| name_str.ends_with(suffix.as_str())) ||
+            configuration::dump_exclude_prefixes()
+            .iter()
+            .any(|prefix| name_str.starts_with(prefix.as_str()));
+        if excluded {
+            return;
+        }
+
+        trace!("[visit_fn] enter name={:?}", name_str);
+
+        match (configuration::dump_mir_proc(), configuration::dump_mir_proc_regex()) {
+            (Some(value), _) => {
+                if name_str != value {
+                    return;
+                }
+            },
+            (None, Some(regex)) => {
+                if !regex.is_match(name_str) {
+                    return;
+                }
+            },
+            (None, None) => {
+                if !configuration::dump_all_functions() {
+                    return;
+                }
+            },
+        };
+
+        let def_id = self.tcx.hir().local_def_id(node_id);
+        let unsupported_terminators = self.find_unsupported_terminators(def_id);
+        if !unsupported_terminators.is_empty() {
+            trace!("[visit_fn] skipping: unsupported terminators {:?}", unsupported_terminators);
+            self.summary.skipped.push(SkippedFunction {
+                name: report_function_name(self.tcx, def_id),
+                reason: format!("unsupported terminators: {}", unsupported_terminators.join(", ")),
+            });
+            self.unimplemented_functions.push(UnimplementedFunction {
+                function: report_function_name(self.tcx, def_id),
+                terminators: unsupported_terminators,
+            });
+            return;
+        }
+        let result = if configuration::dump_catch_panics() {
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| self.dump_fn(def_id, name_str)));
+            match result {
+                Ok(result) => result,
+                Err(payload) => {
+                    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                        message.to_string()
+                    } else if let Some(message) = payload.downcast_ref::<String>() {
+                        message.clone()
+                    } else {
+                        String::from("unknown panic")
+                    };
+                    self.write_panic_graph(def_id, &message)
+                        .expect("Unable to write the placeholder graph");
+                    self.panics.push(PanicRecord {
+                        function: report_function_name(self.tcx, def_id),
+                        message: message.clone(),
+                    });
+                    Err(format!("panic: {}", message))
+                }
+            }
+        } else {
+            self.dump_fn(def_id, name_str)
+        };
+        match result {
+            Ok(function_summary) => {
+                self.summary.functions.push(function_summary);
+            }
+            Err(reason) => {
+                self.summary.skipped.push(SkippedFunction {
+                    name: report_function_name(self.tcx, def_id),
+                    reason: reason,
+                });
+            }
+        }
+
+        trace!("[visit_fn] exit");
+    }
+
+    /// The name of the closure used for selecting it, which consists of the
+    /// qualified name of the enclosing item (as for methods) and the line of
+    /// the closure, for example `<S as T>::foo::closure_at_line_12`. The
+    /// second closure starting on the same line is named
+    /// `closure_at_line_12_2`, and so on.
+    fn closure_name(&mut self, span: Span, node_id: ast::NodeId) -> String {
+        let hir = self.tcx.hir();
+        let parent = hir.get_parent(node_id);
+        let function_name = self.tcx.item_path_str(hir.local_def_id(parent));
+        let line = self.tcx.sess.source_map().lookup_char_pos(span.lo()).line;
+        let count = self.closure_counts.entry((parent, line)).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            format!("{}::closure_at_line_{}", function_name, line)
+        } else {
+            format!("{}::closure_at_line_{}_{}", function_name, line, count)
+        }
+    }
+
+    /// Find the generator terminators, which `visit_terminator` does not
+    /// support.
+    fn find_unsupported_terminators(&self, def_id: DefId) -> Vec<String> {
+        let mir = self.tcx.mir_validated(def_id).borrow();
+        mir.basic_blocks()
+            .iter_enumerated()
+            .filter_map(|(bb, data)| {
+                match data.terminator().kind {
+                    mir::TerminatorKind::Yield { .. } => Some(format!("{:?}: Yield", bb)),
+                    mir::TerminatorKind::GeneratorDrop => Some(format!("{:?}: GeneratorDrop", bb)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Dump the MIR info of the given function. If the function is
+    /// skipped, returns the reason why.
+    fn dump_fn(&self, def_id: DefId, name: &str) -> Result<FunctionSummary, String> {
+        let used_mut_upvars = self.tcx.mir_borrowck(def_id).used_mut_upvars.to_vec();
+
+        // Read Polonius facts.
+        let def_path = self.tcx.hir().def_path(def_id);
+
+        // The Polonius facts always refer to the validated MIR, which is
+        // stolen when the optimized MIR is computed.
+        let (polonius_info, loan_places) = {
+            let validated_mir = self.tcx.mir_validated(def_id).borrow();
+
+            let basic_block_count = validated_mir.basic_blocks().len();
+            if basic_block_count < configuration::dump_min_basic_blocks() {
+                trace!("[dump_fn] skipping: only {} basic blocks", basic_block_count);
+                return Err(format!("only {} basic blocks", basic_block_count));
+            }
+
+            let polonius_info = PoloniusInfo::new(self.tcx, def_id, &validated_mir);
+            let loan_places = polonius_info.get_loan_places(&validated_mir);
+            (polonius_info, loan_places)
+        };
+
+        let validated_mir;
+        let mir: &mir::Mir<'tcx> = match configuration::dump_mir_phase() {
+            MirPhase::Validated => {
+                validated_mir = self.tcx.mir_validated(def_id).borrow();
+                &validated_mir
+            }
+            MirPhase::Optimized => self.tcx.optimized_mir(def_id),
+        };
+
+        let loan_count = polonius_info.loan_count();
+        if loan_count < configuration::dump_min_loan_count() {
+            trace!("[dump_fn] skipping: only {} loans", loan_count);
+            return Err(format!("only {} loans", loan_count));
+        }
+
+        let trivial = polonius_info.borrowck_in_facts.borrow_region.is_empty();
+        if trivial && configuration::dump_skip_trivial() {
+            trace!("[dump_fn] skipping: no borrows");
+            return Err(String::from("no borrows"));
+        }
+        let stub = trivial && configuration::dump_trivial_stub();
+
+        let quiet = configuration::dump_quiet_patterns()
+            .iter()
+            .any(|pattern| matches_glob(name, pattern));
+        let max_blocks_per_file = configuration::dump_max_bb_per_file();
+        let split = !stub && max_blocks_per_file > 0 &&
+            mir.basic_blocks().len() > max_blocks_per_file;
+        let output_dir = PathBuf::from("nll-facts").join(def_path.to_filename_friendly_no_crate());
+        let output_formats = configuration::dump_output_formats();
+        // The main output, which is reported in the summary. A stub is
+        // always written as DOT.
+        let graph_path = match output_formats.first() {
+            Some(&format) if format != OutputFormat::Dot && !stub => {
+                output_dir.join(format!("graph.{}", format.extension()))
+            }
+            _ if split => output_dir.join("graph_index.dot"),
+            _ => output_dir.join("graph.dot"),
+        };
+        let function_summary = FunctionSummary {
+            name: report_function_name(self.tcx, def_id),
+            output_path: if quiet { None } else { Some(graph_path.to_string_lossy().to_string()) },
+            fact_counts: polonius_info.get_fact_counts(),
+        };
+        if quiet {
+            trace!("[dump_fn] not writing the output of a quiet function");
+            return Ok(function_summary);
+        }
+        if stub {
+            let mut graph = File::create(output_dir.join("graph.dot")).expect("Unable to create file");
+            writeln!(graph, "digraph G {{ label = \"Function {}: no borrows\" }}",
+                     to_dot_string!(display_function_name(&def_path)))
+                .expect("Unable to write the stub graph");
+            return Ok(function_summary);
+        }
+        // The Polonius facts refer to the points of the validated MIR, so
+        // they are not shown next to the optimized MIR.
+        let facts_match_mir = configuration::dump_mir_phase() == MirPhase::Validated;
+        let (polonius_info, loan_places) = if facts_match_mir {
+            (polonius_info, loan_places)
+        } else {
+            let no_facts = PoloniusInfo::from_facts(
+                self.tcx, mir, facts::FactLoader::new(), HashMap::new());
+            (no_facts, HashMap::new())
+        };
+        let graph: BufWriter<Box<dyn Write>> = if output_formats.contains(&OutputFormat::Dot) {
+            let first_graph_path = output_dir.join(if split { "graph_0.dot" } else { "graph.dot" });
+            let graph_file = File::create(first_graph_path).expect("Unable to create file");
+            BufWriter::new(Box::new(graph_file))
+        } else {
+            BufWriter::new(Box::new(io::sink()))
+        };
+
+        let initialization = compute_definitely_initialized(mir, self.tcx, def_path.clone());
+        let move_data = if configuration::dump_show_move_paths() {
+            Some(match MoveData::gather_moves(mir, self.tcx) {
+                Ok(move_data) => move_data,
+                Err((move_data, _)) => move_data,
+            })
+        } else {
+            None
+        };
+        let uninitialization = if configuration::dump_show_uninitialized() {
+            Some(compute_definitely_uninitialized(mir, self.tcx))
+        } else {
+            None
+        };
+        let maybe_initialization = if configuration::dump_show_maybe_initialized() {
+            Some(compute_maybe_initialized(mir, self.tcx))
+        } else {
+            None
+        };
+
+        let uses_after_drop = find_uses_after_drop(mir);
+        for use_after_drop in uses_after_drop.iter() {
+            self.tcx.sess.span_warn(
+                mir.source_info(use_after_drop.move_location).span,
+                &format!("{:?} is moved after being dropped at {:?}",
+                         use_after_drop.local, use_after_drop.drop_location));
+        }
+
+        if facts_match_mir && configuration::dump_verify_cfg_edges() &&
+                configuration::dump_load_relation("cfg_edge") {
+            let (missing_in_graph, missing_in_facts) = compare_cfg_edges(mir, &polonius_info);
+            for (source, target) in missing_in_graph {
+                self.tcx.sess.span_warn(
+                    mir.span,
+                    &format!("cfg_edge facts contain the edge {:?} -> {:?}, which is not in the graph",
+                             source, target));
+            }
+            for (source, target) in missing_in_facts {
+                self.tcx.sess.span_warn(
+                    mir.span,
+                    &format!("the graph contains the edge {:?} -> {:?}, which is not in the cfg_edge facts",
+                             source, target));
+            }
+        }
+
+        let region_importance = compute_region_importance(&polonius_info);
+        let mut conflicting_loans: HashMap<_, Vec<_>> = HashMap::new();
+        if configuration::dump_show_conflicting_loans() {
+            for (first, second, point) in polonius_info.find_conflicting_loans(&loan_places) {
+                conflicting_loans.entry(point).or_default().push((first, second));
+            }
+        }
+        let mut mir_info_printer = MirInfoPrinter {
+            def_path: def_path,
+            tcx: self.tcx,
+            param_env: self.tcx.param_env(def_id),
+            mir: mir,
+            graph: cell::RefCell::new(graph),
+            block_range: 0..mir.basic_blocks().len(),
+            virtual_targets: cell::RefCell::new(BTreeSet::new()),
+            initialization: initialization,
+            uninitialization: uninitialization,
+            maybe_initialization: maybe_initialization,
+            move_data: move_data,
+            polonius_info: polonius_info,
+            loops: ProcedureLoops::new(mir),
+            aliases: compute_aliases(mir),
+            uses_after_drop: uses_after_drop,
+            edge_styles: configuration::dump_edge_styles(),
+            used_mut_upvars: used_mut_upvars,
+            predecessors: compute_predecessors(mir),
+            read_locals: compute_read_locals(mir),
+            region_importance: region_importance,
+            representatives: if configuration::dump_aggregate_identical() {
+                find_identical_blocks(mir)
+            } else {
+                HashMap::new()
+            },
+            conflicting_loans: conflicting_loans,
+            loan_places: loan_places,
+            escape_unicode: configuration::dump_escape_unicode(),
+        };
+        if output_formats.contains(&OutputFormat::Dot) {
+            // The paths of the written DOT files, which are rendered below.
+            let mut dot_paths = Vec::new();
+            if split {
+                let file_count = (mir.basic_blocks().len() + max_blocks_per_file - 1) / max_blocks_per_file;
+                for file_index in 0..file_count {
+                    let path = output_dir.join(format!("graph_{}.dot", file_index));
+                    if file_index > 0 {
+                        let graph_file = File::create(&path).expect("Unable to create file");
+                        *mir_info_printer.graph.borrow_mut() = BufWriter::new(Box::new(graph_file));
+                    }
+                    let start = file_index * max_blocks_per_file;
+                    let end = (start + max_blocks_per_file).min(mir.basic_blocks().len());
+                    mir_info_printer.block_range = start..end;
+                    mir_info_printer.print_info().unwrap();
+                    dot_paths.push(path);
+                }
+                let index_path = output_dir.join("graph_index.dot");
+                mir_info_printer.print_graph_index(&index_path).unwrap();
+                dot_paths.push(index_path);
+            } else {
+                mir_info_printer.print_info().unwrap();
+                dot_paths.push(output_dir.join("graph.dot"));
+            }
+            mir_info_printer.graph.borrow_mut().flush().unwrap();
+            for dot_path in dot_paths.iter() {
+                if configuration::dump_output_svg() {
+                    self.render_graph(dot_path, "svg");
+                }
+                if configuration::dump_output_png() {
+                    self.render_graph(dot_path, "png");
+                }
+            }
+        }
+        if output_formats.iter().any(|&format| format != OutputFormat::Dot) {
+            let graph_info = mir_info_printer.collect_graph_info();
+            if output_formats.contains(&OutputFormat::Json) {
+                let json_printer = JsonInfoPrinter { graph_info: &graph_info };
+                json_printer.print(&output_dir.join("graph.json")).unwrap();
+            }
+            if output_formats.contains(&OutputFormat::Html) {
+                write_html_output(&graph_info,
+                                  &mir_info_printer.function_signature(),
+                                  &self.tcx.crate_name(LOCAL_CRATE).as_str(),
+                                  &output_dir.join("graph.html")).unwrap();
+            }
+            if output_formats.contains(&OutputFormat::Text) {
+                let text_printer = TextInfoPrinter { graph_info: &graph_info };
+                text_printer.print(&output_dir.join("graph.txt")).unwrap();
+            }
+        }
+        if facts_match_mir {
+            mir_info_printer.print_loan_intervals(&output_dir.join("loan_intervals.txt")).unwrap();
+        }
+        if facts_match_mir && configuration::dump_animate_subset() {
+            mir_info_printer.print_subset_per_block(&output_dir).unwrap();
+        }
+        if configuration::dump_generate_repro() {
+            mir_info_printer.print_repro(&output_dir.join("repro.rs")).unwrap();
+        }
+        if facts_match_mir && configuration::dump_export_timeline() {
+            mir_info_printer.print_timeline(&output_dir.join("timeline.json")).unwrap();
+        }
+        if facts_match_mir && configuration::dump_export_region_constraints() {
+            mir_info_printer.print_region_constraints(&output_dir.join("region_constraints.dot")).unwrap();
+        }
+        if facts_match_mir && configuration::dump_export_facts_csv() {
+            export_facts_to_csv(&mir_info_printer.polonius_info,
+                                &display_function_name(&mir_info_printer.def_path),
+                                &output_dir.join("facts_csv")).unwrap();
+        }
+        Ok(function_summary)
+    }
+
+    /// Render the graphviz file with `dot` into a file with the same name
+    /// and the extension `format`. Failures, for example because `dot` is
+    /// not installed, are reported as warnings.
+    fn render_graph(&self, dot_path: &Path, format: &str) {
+        let output_path = dot_path.with_extension(format);
+        let result = process::Command::new("dot")
+            .arg(format!("-T{}", format))
+            .arg("-o")
+            .arg(&output_path)
+            .arg(dot_path)
+            .status();
+        match result {
+            Ok(ref status) if status.success() => {}
+            Ok(status) => {
+                self.tcx.sess.warn(&format!(
+                    "dot failed to render {} ({})", dot_path.display(), status));
+            }
+            Err(error) => {
+                self.tcx.sess.warn(&format!(
+                    "unable to run dot to render {}: {}", dot_path.display(), error));
+            }
+        }
+    }
+
+    /// Write a graph that consists of a single node with the message of
+    /// the panic that happened while dumping the function.
+    fn write_panic_graph(&self, def_id: DefId, message: &str) -> Result<(),io::Error> {
+        let def_path = self.tcx.hir().def_path(def_id);
+        let graph_path = PathBuf::from("nll-facts")
+            .join(def_path.to_filename_friendly_no_crate())
+            .join("graph.dot");
+        let mut graph = BufWriter::new(File::create(graph_path)?);
+        writeln!(graph, "digraph G {{")?;
+        writeln!(graph, "\"error\" [ shape = \"box\" color = \"red\" label = \"{}\" ];",
+                 to_dot_string!(message))?;
+        writeln!(graph, "}}")?;
+        Ok(())
+    }
+}
+
+/// The name of the function as shown in the output, which is replaced
+/// with a hash if `DUMP_REDACT_FUNCTION_NAMES` is set.
+fn display_function_name(def_path: &hir::map::DefPath) -> String {
+    let name = def_path.to_string_no_crate();
+    if configuration::dump_redact_function_names() {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        format!("fn_{:016x}", hasher.finish())
+    } else {
+        name
+    }
+}
+
+/// The qualified name of the function as written to the summary and the
+/// other reports, for example `<S as T>::foo`. It is redacted in the same
+/// way as `display_function_name`.
+fn report_function_name(tcx: TyCtxt, def_id: DefId) -> String {
+    if configuration::dump_redact_function_names() {
+        display_function_name(&tcx.def_path(def_id))
+    } else {
+        tcx.item_path_str(def_id)
+    }
+}
+
+/// The HTML template used when `DUMP_HTML_TEMPLATE` is not set.
+const DEFAULT_HTML_TEMPLATE: &str = include_str!("html_template.html");
+
+/// Write the collected information as a web page with one table per basic
+/// block. With `DUMP_SHOW_BB_NAVIGATION`, each table links to the
+/// predecessors and successors of its block and a sidebar lists all blocks.
+/// The tables are substituted for `{{GRAPH_HTML}}` in the template set by
+/// `DUMP_HTML_TEMPLATE`, which can also contain the other placeholders
+/// described there.
+fn write_html_output(graph_info: &GraphInfo, signature: &str, crate_name: &str,
+                     path: &Path) -> Result<(),io::Error> {
+    let mut file = Vec::new();
+    let navigation = configuration::dump_show_bb_navigation();
+    // The anchors are named after the indices of the blocks, for example
+    // `BB3` for `bb3`.
+    let indices: HashMap<&str, usize> = graph_info.blocks
+        .iter()
+        .map(|block| (block.block.as_str(), block.index))
+        .collect();
+    let link = |block: &str| match indices.get(block) {
+        Some(index) => format!("<a href=\"#BB{}\">{}</a>", index, block),
+        None => escape_attribute(block),
+    };
+    let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for block in &graph_info.blocks {
+        for successor in &block.successors {
+            let blocks = predecessors.entry(successor.as_str()).or_insert_with(Vec::new);
+            if !blocks.contains(&block.block.as_str()) {
+                blocks.push(block.block.as_str());
+            }
+        }
+    }
+    if navigation {
+        writeln!(file, "<nav style=\"position: fixed; top: 0; right: 0; padding: 1em; \
+                        max-height: 100%; overflow-y: auto; background: white\">")?;
+        for block in &graph_info.blocks {
+            writeln!(file, "{}<br/>", link(block.block.as_str()))?;
+        }
+        writeln!(file, "</nav>")?;
+    }
+    for block in &graph_info.blocks {
+        writeln!(file, "<h2 id=\"{0}\">{0}</h2>", block.block)?;
+        writeln!(file, "<p>Definitely initialized: {}</p>",
+                 escape_attribute(&block.initialized_before.join(", ")))?;
+        if navigation {
+            writeln!(file, "<a id=\"BB{}\"></a>", block.index)?;
+        }
+        writeln!(file, "<table border=\"1\">")?;
+        if navigation {
+            let links: Vec<_> = predecessors
+                .get(block.block.as_str())
+                .map(|blocks| blocks.iter().map(|block| link(*block)).collect())
+                .unwrap_or_default();
+            writeln!(file, "<tr><td colspan=\"9\">← prev blocks: {}</td></tr>", links.join(", "))?;
+        }
+        writeln!(file, "<tr><th>Location</th><th>Statement</th>\
+                        <th>Loans (start)</th><th>Loans (mid)</th>\
+                        <th>Borrow Regions (start)</th><th>Borrow Regions (mid)</th>\
+                        <th>Regions (start)</th><th>Regions (mid)</th>\
+                        <th>Definitely Initialized</th></tr>")?;
+        for statement in &block.statements {
+            let cells = [
+                &statement.loans_start,
+                &statement.loans_mid,
+                &statement.borrow_regions_start,
+                &statement.borrow_regions_mid,
+                &statement.regions_start,
+                &statement.regions_mid,
+                &statement.initialized_after,
+            ];
+            write!(file, "<tr><td>{}</td><td>{}</td>",
+                   statement.location, escape_attribute(&statement.text))?;
+            for cell in cells.iter() {
+                write!(file, "<td>{}</td>", escape_attribute(&cell.join(", ")))?;
+            }
+            writeln!(file, "</tr>")?;
+        }
+        if navigation {
+            let links: Vec<_> = block.successors.iter().map(|block| link(block.as_str())).collect();
+            writeln!(file, "<tr><td colspan=\"9\">→ next blocks: {} \
+                            (<a href=\"#top\">back to top</a>)</td></tr>", links.join(", "))?;
+        }
+        writeln!(file, "</table>")?;
+        let successors: Vec<_> = block.successors
+            .iter()
+            .map(|successor| format!("<a href=\"#{0}\">{0}</a>", successor))
+            .collect();
+        writeln!(file, "<p>Successors: {}</p>", successors.join(", "))?;
+    }
+    let graph_html = String::from_utf8(file).unwrap();
+
+    let template = match configuration::dump_html_template() {
+        Some(template_path) => fs::read_to_string(template_path)?,
+        None => DEFAULT_HTML_TEMPLATE.to_string(),
+    };
+    // A `</script>` in the JSON would end the script element that holds it.
+    let graph_json = serde_json::to_string(graph_info)?.replace("</", "<\\/");
+    // The graph is substituted last, so that placeholders in it are kept.
+    let page = template
+        .replace("{{FUNCTION_NAME}}", &escape_attribute(&graph_info.function))
+        .replace("{{FUNCTION_SIGNATURE}}", &escape_attribute(signature))
+        .replace("{{CRATE_NAME}}", &escape_attribute(crate_name))
+        .replace("{{TIMESTAMP}}", &get_timestamp().to_string())
+        .replace("{{GRAPH_JSON}}", &graph_json)
+        .replace("{{GRAPH_HTML}}", &graph_html);
+    fs::write(path, page)
+}
+
+/// Format the items and sort them.
+fn sorted_strings<T: fmt::Debug>(items: impl Iterator<Item = T>) -> Vec<String> {
+    let mut strings: Vec<_> = items.map(|item| format!("{:?}", item)).collect();
+    strings.sort();
+    strings.dedup();
+    strings
+}
+
+/// Format the type as the compiler does in its messages, for example
+/// `std::vec::Vec<u8>`. The printer of this compiler version takes the type
+/// context from the thread-local storage, so it can only be used while the
+/// analysis is running.
+fn pretty_ty(ty: ty::Ty) -> String {
+    format!("{}", ty)
+}
+
+/// Replace the generic arguments of a printed type that are nested in
+/// `max_depth` or more brackets with `...`. For example, with the depth 2,
+/// `Option<Result<Vec<u8>, E>>` becomes `Option<Result<...>>`. `0` means
+/// no limit.
+fn truncate_type_depth(text: &str, max_depth: usize) -> String {
+    /// Print the text nested in `depth` brackets that starts at `index`
+    /// and stop at the closing bracket.
+    fn truncate(chars: &[char], index: &mut usize, depth: usize, max_depth: usize,
+                result: &mut String) {
+        while *index < chars.len() {
+            let c = chars[*index];
+            let is_arrow = c == '>' && *index > 0 && chars[*index - 1] == '-';
+            match c {
+                '<' | '(' | '[' => {
+                    *index += 1;
+                    if depth < max_depth {
+                        result.push(c);
+                        let is_empty = *index < chars.len() && ")]>".contains(chars[*index]);
+                        if depth + 1 == max_depth && !is_empty {
+                            result.push_str("...");
+                        }
+                    }
+                    truncate(chars, index, depth + 1, max_depth, result);
+                    if *index < chars.len() {
+                        if depth < max_depth {
+                            result.push(chars[*index]);
+                        }
+                        *index += 1;
+                    }
+                }
+                '>' | ')' | ']' if !is_arrow => return,
+                _ => {
+                    if depth < max_depth {
+                        result.push(c);
+                    }
+                    *index += 1;
+                }
+            }
+        }
+    }
+    if max_depth == 0 {
+        return text.to_string();
+    }
+    let chars: Vec<_> = text.chars().collect();
+    let mut result = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        truncate(&chars, &mut index, 0, max_depth, &mut result);
+        if index < chars.len() {
+            // An unbalanced closing bracket.
+            result.push(chars[index]);
+            index += 1;
+        }
+    }
+    result
+}
+
+/// Check whether `name` matches the glob `pattern`, in which `*` matches
+/// any sequence of characters and `?` matches any single character.
+fn matches_glob(name: &str, pattern: &str) -> bool {
+    let regex = regex::escape(pattern)
+        .replace("\\*", ".*")
+        .replace("\\?", ".");
+    Regex::new(&format!("^{}$", regex)).unwrap().is_match(name)
+}
+
+/// The longest directory that contains all source files of the crate.
+fn common_source_prefix(tcx: TyCtxt) -> PathBuf {
+    let mut prefix: Option<PathBuf> = None;
+    for file in tcx.sess.source_map().files().iter() {
+        if file.is_imported() {
+            continue;
+        }
+        if let FileName::Real(ref path) = file.name {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            prefix = Some(match prefix {
+                None => dir.to_path_buf(),
+                Some(prefix) => prefix
+                    .components()
+                    .zip(dir.components())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+            });
+        }
+    }
+    prefix.unwrap_or_default()
+}
+
+/// Format the path of a source file as configured by
+/// `DUMP_STRIP_PATH_PREFIX`, `DUMP_SHOW_FULL_PATHS`, and `DUMP_REDACT_PATHS`. The separators are
+/// always shown as `/`, so that the graphs do not depend on the platform.
+fn source_path_to_string(tcx: TyCtxt, filename: &FileName) -> String {
+    let mut path: &Path = match filename {
+        FileName::Real(path) => path,
+        _ => return filename.to_string(),
+    };
+    let current_dir = env::current_dir().unwrap_or_default();
+    if configuration::dump_redact_paths() && path.is_absolute() {
+        match path.strip_prefix(&current_dir) {
+            Ok(relative_path) => path = relative_path,
+            Err(_) => {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                return format!("<redacted>/{}", file_name);
+            }
+        }
+    }
+    let path = if configuration::dump_show_full_paths() {
+        let prefix = configuration::dump_strip_path_prefix()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| common_source_prefix(tcx));
+        path.strip_prefix(&prefix).unwrap_or(path)
+    } else {
+        path.file_name().map(Path::new).unwrap_or(path)
+    };
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Escape the non-ASCII characters of a graph line as HTML entities if
+/// `escape_unicode` (`DUMP_ESCAPE_UNICODE`) is set. Graphviz resolves the
+/// entities both in HTML and in plain labels.
+fn encode_output(line: String, escape_unicode: bool) -> String {
+    if !escape_unicode || line.is_ascii() {
+        return line;
+    }
+    let mut result = String::with_capacity(line.len());
+    for c in line.chars() {
+        if c.is_ascii() {
+            result.push(c);
+        } else {
+            result.push_str(&format!("&#{};", c as u32));
+        }
+    }
+    result
+}
+
+lazy_static! {
+    static ref BREAK_REGEX: Regex = Regex::new(r"<br\s*/?>").unwrap();
+    static ref TAG_REGEX: Regex = Regex::new(r"<[^>]*>").unwrap();
+    /// A path with at least two segments, for example `std::vec::Vec`.
+    static ref PATH_REGEX: Regex =
+        Regex::new(r"[A-Za-z_][A-Za-z0-9_]*(::[A-Za-z_][A-Za-z0-9_]*)+").unwrap();
+}
+
+/// Convert HTML content back to the plain text it displays. Line breaks
+/// are converted to newlines.
+fn html_to_plain_text(html: &str) -> String {
+    let text = BREAK_REGEX.replace_all(html, "\n");
+    TAG_REGEX.replace_all(&text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Escape the text so that it can be used as a value of an HTML attribute.
+/// Line breaks are kept as `&#10;`, so that multi-line tooltips are shown
+/// on several lines.
+pub(crate) fn escape_attribute(text: &str) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+        .replace("'", "&#39;")
+        .replace("\r\n", "\n")
+        .replace("\n", "&#10;")
+        .replace("\t", "&#9;")
+}
+
+/// Build the `title` attribute that shows the full text of a cell.
+fn build_tooltip(full_text: &str) -> String {
+    format!("title=\"{}\"", escape_attribute(full_text))
+}
+
+/// Format a table cell as described in `write_cell!`.
+fn with_tooltip(content: &str, attributes: &str, tooltip: Option<&str>) -> String {
+    let max_chars = configuration::dump_max_cell_chars();
+    let plain_text = html_to_plain_text(content);
+    let truncated = max_chars > 0 && plain_text.chars().count() > max_chars;
+    let mut attributes = if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", attributes)
+    };
+    match tooltip {
+        Some(tooltip) if !tooltip.is_empty() => {
+            attributes.push_str(&format!(" {}", build_tooltip(tooltip)));
+        }
+        Some(_) => {}
+        None if truncated => {
+            attributes.push_str(&format!(" {}", build_tooltip(&plain_text)));
+        }
+        None => {}
+    }
+    if truncated {
+        format!("<td{}>{}</td>", attributes, truncate_html(content, max_chars))
+    } else {
+        format!("<td{}>{}</td>", attributes, content)
+    }
+}
+
+/// Truncate the HTML content to `max_chars` characters of its plain text.
+/// The markup of the kept part is preserved and the tags that are still
+/// open are closed after the `…`.
+fn truncate_html(content: &str, max_chars: usize) -> String {
+    let mut result = String::new();
+    let mut open_tags = Vec::new();
+    let mut chars = 0;
+    let mut rest = content;
+    while chars < max_chars && !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let tag = &rest[1..end];
+            if tag.starts_with('/') {
+                open_tags.pop();
+            } else if tag.ends_with('/') {
+                // A line break, which is one character of the plain text.
+                chars += 1;
+            } else {
+                open_tags.push(tag.split_whitespace().next().unwrap_or(""));
+            }
+            result.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+        } else {
+            // An entity such as `&lt;` is one character of the plain text.
+            let length = if rest.starts_with('&') {
+                rest.find(';').map_or(1, |end| end + 1)
+            } else {
+                rest.chars().next().unwrap().len_utf8()
+            };
+            result.push_str(&rest[..length]);
+            rest = &rest[length..];
+            chars += 1;
+        }
+    }
+    result.push('…');
+    for tag in open_tags.iter().rev() {
+        result.push_str(&format!("</{}>", tag));
+    }
+    result
+}
+
+/// Join the items of a sorted list. If the result would be longer than
+/// `DUMP_MAX_CELL_CHARS`, the remaining items are summarized as
+/// `... +N more`.
+fn join_truncated(items: Vec<String>) -> String {
+    join_truncated_list(items).0
+}
+
+/// Join the items as `join_truncated` does. If some items are summarized,
+/// the full list is returned too as plain text, to be used as the tooltip
+/// of the cell.
+fn join_truncated_list(items: Vec<String>) -> (String, Option<String>) {
+    let max_chars = configuration::dump_max_cell_chars();
+    let mut result = String::new();
+    let mut length = 0;
+    for (index, item) in items.iter().enumerate() {
+        let item_length = html_to_plain_text(item).chars().count();
+        let separator = if index == 0 { "" } else { ", " };
+        if max_chars > 0 && index > 0 && length + separator.len() + item_length > max_chars {
+            result.push_str(&format!(", ... +{} more", items.len() - index));
+            let full_text = items.iter()
+                .map(|item| html_to_plain_text(item))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return (result, Some(full_text));
+        }
+        result.push_str(separator);
+        result.push_str(item);
+        length += separator.len() + item_length;
+    }
+    (result, None)
+}
+
+/// The number of seconds since the Unix epoch.
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Collects the locals that are read somewhere in the MIR body.
+struct ReadLocalsCollector {
+    locals: HashSet<mir::Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for ReadLocalsCollector {
+    fn visit_local(&mut self, local: &mir::Local, context: PlaceContext<'tcx>,
+                   _location: mir::Location) {
+        if context.is_nonmutating_use() {
+            self.locals.insert(*local);
+        }
+    }
+}
+
+/// Find the locals that are read somewhere in the MIR body.
+fn compute_read_locals(mir: &mir::Mir) -> HashSet<mir::Local> {
+    let mut collector = ReadLocalsCollector { locals: HashSet::new() };
+    collector.visit_mir(mir);
+    collector.locals
+}
+
+/// Compute the predecessors of each basic block by inverting the edges
+/// of the control flow graph. The predecessors are sorted by their index.
+fn compute_predecessors(mir: &mir::Mir) -> HashMap<mir::BasicBlock, Vec<mir::BasicBlock>> {
+    let mut predecessors: HashMap<_, Vec<_>> = HashMap::new();
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        if let Some(ref terminator) = data.terminator {
+            for &successor in terminator.successors() {
+                let entry = predecessors.entry(successor).or_default();
+                if !entry.contains(&bb) {
+                    entry.push(bb);
+                }
+            }
+        }
+    }
+    predecessors
+}
+
+/// Compare the edges between basic blocks in the `cfg_edge` facts with the
+/// successors of the terminators, which are the edges drawn by
+/// `visit_terminator`. Returns the edges that are only in the facts and the
+/// edges that are only in the graph.
+fn compare_cfg_edges(
+    mir: &mir::Mir,
+    polonius_info: &PoloniusInfo,
+) -> (Vec<(mir::BasicBlock, mir::BasicBlock)>, Vec<(mir::BasicBlock, mir::BasicBlock)>) {
+    let interner = &polonius_info.interner;
+    let fact_edges: BTreeSet<_> = polonius_info.borrowck_in_facts.cfg_edge
+        .iter()
+        .map(|&(source, target)| (interner.get_point(source), interner.get_point(target)))
+        .filter(|(source, target)| {
+            // An edge between blocks leads from the mid point of a terminator
+            // to the start point of the first statement.
+            source.typ == facts::PointType::Mid &&
+                source.location.statement_index == mir[source.location.block].statements.len() &&
+                target.typ == facts::PointType::Start &&
+                target.location.statement_index == 0
+        })
+        .map(|(source, target)| (source.location.block, target.location.block))
+        .collect();
+    let graph_edges: BTreeSet<_> = mir.basic_blocks()
+        .iter_enumerated()
+        .flat_map(|(bb, data)| {
+            data.terminator().successors().map(move |&target| (bb, target))
+        })
+        .collect();
+    (
+        fact_edges.difference(&graph_edges).cloned().collect(),
+        graph_edges.difference(&fact_edges).cloned().collect(),
+    )
+}
+
+/// Group the basic blocks whose statements are identical apart from their
+/// spans. Returns a map from each block of a group with at least two blocks
+/// to the first block of its group. Blocks without statements are never
+/// grouped.
+fn find_identical_blocks(mir: &mir::Mir) -> HashMap<mir::BasicBlock, mir::BasicBlock> {
+    let mut groups: HashMap<Vec<String>, Vec<mir::BasicBlock>> = HashMap::new();
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        if data.statements.is_empty() {
+            continue;
+        }
+        let key = data.statements.iter().map(|statement| format!("{:?}", statement)).collect();
+        groups.entry(key).or_default().push(bb);
+    }
+    let mut representatives = HashMap::new();
+    for group in groups.values().filter(|group| group.len() > 1) {
+        for &bb in group {
+            representatives.insert(bb, group[0]);
+        }
+    }
+    representatives
+}
+
+/// Count the program points at which each region is live.
+fn compute_region_importance(polonius_info: &PoloniusInfo) -> HashMap<facts::Region, usize> {
+    let mut importance = HashMap::new();
+    for &(region, _) in polonius_info.borrowck_in_facts.region_live_at.iter() {
+        *importance.entry(region).or_insert(0) += 1;
+    }
+    importance
+}
 
-    trace!("[dump_info] exit");
+/// Replace the locals that are dereferenced in the place with the locals
+/// whose values they hold.
+fn substitute_aliases<'tcx>(place: &mir::Place<'tcx>, aliases: &Aliases) -> mir::Place<'tcx> {
+    match *place {
+        mir::Place::Projection(box mir::Projection {
+            base: mir::Place::Local(local),
+            elem: mir::ProjectionElem::Deref,
+        }) if aliases.contains_key(&local) => {
+            mir::Place::Local(aliases[&local]).deref()
+        }
+        mir::Place::Projection(box mir::Projection { ref base, ref elem }) => {
+            substitute_aliases(base, aliases).elem(elem.clone())
+        }
+        _ => place.clone(),
+    }
 }
 
-struct InfoPrinter<'a, 'tcx: 'a> {
+struct MirInfoPrinter<'a, 'tcx: 'a> {
+    pub def_path: hir::map::DefPath,
     pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    pub param_env: ty::ParamEnv<'tcx>,
+    pub mir: &'a mir::Mir<'tcx>,
+    /// The DOT output. It is a sink if the DOT format is not requested.
+    pub graph: cell::RefCell<BufWriter<Box<dyn Write>>>,
+    /// The indices of the basic blocks written into the current graph file.
+    pub block_range: Range<usize>,
+    /// The basic blocks in other graph files that are targets of edges
+    /// from the current graph file.
+    pub virtual_targets: cell::RefCell<BTreeSet<mir::BasicBlock>>,
+    pub initialization: DefinitelyInitializedAnalysisResult<'tcx>,
+    /// The definitely uninitialized places, computed only if
+    /// `DUMP_SHOW_UNINITIALIZED` is set.
+    pub uninitialization: Option<DefinitelyUninitializedAnalysisResult<'tcx>>,
+    /// The maybe initialized places, computed only if
+    /// `DUMP_SHOW_MAYBE_INITIALIZED` is set.
+    pub maybe_initialization: Option<MaybeInitializedAnalysisResult<'tcx>>,
+    /// The move paths of the places, computed only if
+    /// `DUMP_SHOW_MOVE_PATHS` is set.
+    pub move_data: Option<MoveData<'tcx>>,
+    pub polonius_info: PoloniusInfo,
+    pub loops: ProcedureLoops,
+    pub aliases: AliasAnalysisResult,
+    /// Locals that are moved after being dropped.
+    pub uses_after_drop: Vec<UseAfterDrop>,
+    /// Graphviz attributes of the edges of each kind.
+    pub edge_styles: HashMap<String, String>,
+    /// Upvars that the borrow checker found to be used mutably.
+    pub used_mut_upvars: Vec<mir::Field>,
+    /// The predecessors of each basic block.
+    pub predecessors: HashMap<mir::BasicBlock, Vec<mir::BasicBlock>>,
+    /// The locals that are read somewhere in the function.
+    pub read_locals: HashSet<mir::Local>,
+    /// The number of program points at which each region is live.
+    pub region_importance: HashMap<facts::Region, usize>,
+    /// The blocks that are collapsed into the node of another block with
+    /// identical statements, mapped to that block.
+    pub representatives: HashMap<mir::BasicBlock, mir::BasicBlock>,
+    /// The assignments that created the loans.
+    pub loan_places: HashMap<facts::Loan, LoanPlaces<'tcx>>,
+    /// The pairs of conflicting loans at each point, computed only if
+    /// `DUMP_SHOW_CONFLICTING_LOANS` is set.
+    pub conflicting_loans: HashMap<facts::PointIndex, Vec<(facts::Loan, facts::Loan)>>,
+    /// `DUMP_ESCAPE_UNICODE`, which is read once instead of for every line.
+    pub escape_unicode: bool,
 }
 
-impl<'a, 'tcx> intravisit::Visitor<'tcx> for InfoPrinter<'a, 'tcx> {
-    fn nested_visit_map<'this>(&'this mut self) -> intravisit::NestedVisitorMap<'this, 'tcx> {
-        let map = self.tcx.hir();
-        intravisit::NestedVisitorMap::All(map)
+impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
+
+    pub fn print_info(&mut self) -> Result<(),io::Error> {
+        if configuration::dump_add_file_header() {
+            self.print_file_header()?;
+        }
+        write_graph!(self, "digraph G {{ rankdir={}\n", configuration::dump_graph_direction());
+        let edge_fontsize = configuration::dump_edge_fontsize();
+        if !edge_fontsize.is_empty() {
+            write_graph!(self, "edge [ fontsize = \"{}\" ];", to_dot_string!(edge_fontsize));
+        }
+        let is_first_file = self.block_range.start == 0;
+        if is_first_file {
+            self.print_title()?;
+            self.print_function_header()?;
+            if self.shows_facts() && configuration::dump_show_loan_summary() {
+                self.print_loan_summary()?;
+            }
+            if configuration::dump_show_config_node() {
+                self.print_config_node()?;
+            }
+        }
+        if configuration::dump_cluster_loops() {
+            for &loop_head in self.loops.loop_heads.iter() {
+                if self.loops.get_loop_depth(loop_head) == 1 {
+                    self.print_loop_cluster(loop_head)?;
+                }
+            }
+        }
+        for bb in self.mir.basic_blocks().indices() {
+            if self.is_block_visible(bb) && self.representative(bb) == bb {
+                self.visit_basic_block(bb)?;
+            }
+        }
+        self.print_virtual_targets()?;
+        if is_first_file {
+            self.print_temp_variables()?;
+            self.print_loans()?;
+        }
+        write_graph!(self, "}}\n");
+        Ok(())
     }
 
-    fn visit_fn(&mut self, fk: intravisit::FnKind<'tcx>, _fd: &'tcx hir::FnDecl,
-                _b: hir::BodyId, _s: Span, node_id: ast::NodeId) {
-        let name = match fk {
-            intravisit::FnKind::ItemFn(name, ..) => name,
-            _ => return,
+    /// The identifier of the node of the basic block, formatted as
+    /// configured by `DUMP_NODE_ID_FORMAT`. The identifier contains only
+    /// letters, digits, and underscores, which all DOT parsers accept.
+    fn node_id(&self, bb: mir::BasicBlock) -> String {
+        let bb = self.representative(bb);
+        let id = match configuration::dump_node_id_format() {
+            NodeIdFormat::Name => format!("{:?}", bb),
+            NodeIdFormat::Index => bb.index().to_string(),
+            NodeIdFormat::Path if configuration::dump_redact_function_names() => {
+                format!("{}_{:?}", self.function_name(), bb)
+            }
+            NodeIdFormat::Path => {
+                format!("fn_{}_{:?}", self.def_path.to_filename_friendly_no_crate(), bb)
+            }
         };
-        if name.to_string().ends_with("__spec") {
-            // We ignore spec functions.
-            return;
+        id.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect()
+    }
+
+    /// The block whose node represents the basic block. Differs from the
+    /// block only if `DUMP_AGGREGATE_IDENTICAL` collapsed it.
+    fn representative(&self, bb: mir::BasicBlock) -> mir::BasicBlock {
+        self.representatives.get(&bb).cloned().unwrap_or(bb)
+    }
+
+    /// The name of the block shown in its node, with the number of blocks
+    /// collapsed into it.
+    fn block_name(&self, bb: mir::BasicBlock) -> String {
+        let count = self.representatives
+            .values()
+            .filter(|&&representative| representative == bb)
+            .count();
+        if count > 1 {
+            format!("{:?} (×{} identical blocks)", bb, count)
+        } else {
+            format!("{:?}", bb)
         }
+    }
+
+    /// Print the edges of the blocks collapsed into the node of `bb`. Edges
+    /// of a block are skipped if a previous block of the group has a
+    /// terminator of the same kind with the same targets.
+    fn visit_collapsed_terminators(&self, bb: mir::BasicBlock) -> Result<(),io::Error> {
+        let mut members: Vec<_> = self.representatives
+            .iter()
+            .filter(|&(&member, &representative)| representative == bb && member != bb)
+            .map(|(&member, _)| member)
+            .collect();
+        if members.is_empty() {
+            return Ok(());
+        }
+        members.sort();
+        let edge_key = |terminator: &mir::Terminator| {
+            let targets: Vec<_> = terminator.successors()
+                .map(|&target| self.representative(target))
+                .collect();
+            (mem::discriminant(&terminator.kind), targets)
+        };
+        let mut seen = vec![edge_key(self.mir[bb].terminator())];
+        for member in members {
+            let terminator = self.mir[member].terminator();
+            let key = edge_key(terminator);
+            if !seen.contains(&key) {
+                self.visit_terminator(member, terminator)?;
+                seen.push(key);
+            }
+        }
+        Ok(())
+    }
 
-        trace!("[visit_fn] enter name={:?}", name);
+    /// Is the basic block written into the current graph file?
+    fn is_block_visible(&self, bb: mir::BasicBlock) -> bool {
+        self.block_range.start <= bb.index() && bb.index() < self.block_range.end
+    }
 
-        match configuration::dump_mir_proc() {
-            Some(value) => {
-                if name != value {
-                    return;
+    /// The index of the graph file that contains the basic block.
+    fn get_file_index(&self, bb: mir::BasicBlock) -> usize {
+        let max_blocks_per_file = configuration::dump_max_bb_per_file();
+        if max_blocks_per_file == 0 {
+            0
+        } else {
+            bb.index() / max_blocks_per_file
+        }
+    }
+
+    /// The identifier of the node that represents the target of an edge.
+    /// Targets in other graph files are represented by virtual nodes.
+    fn edge_target<B: Borrow<mir::BasicBlock>>(&self, target: B) -> String {
+        let target = self.representative(*target.borrow());
+        if self.is_block_visible(target) {
+            format!("\"{}\"", self.node_id(target))
+        } else {
+            self.virtual_targets.borrow_mut().insert(target);
+            format!("\"goto_file_{}_{:?}\"", self.get_file_index(target), target)
+        }
+    }
+
+    /// Print the virtual nodes that represent the targets of the edges
+    /// that lead into other graph files.
+    fn print_virtual_targets(&self) -> Result<(),io::Error> {
+        let targets: Vec<_> = self.virtual_targets.borrow_mut().iter().cloned().collect();
+        self.virtual_targets.borrow_mut().clear();
+        for target in targets {
+            let file_index = self.get_file_index(target);
+            write_graph!(self, "\"goto_file_{}_{:?}\" [ shape = \"note\" label = \"graph_{}.dot\\n{:?}\" ];",
+                         file_index, target, file_index, target);
+        }
+        Ok(())
+    }
+
+    /// Write a graph whose nodes are the graph files of a split function and
+    /// whose edges show which files have edges between them.
+    pub fn print_graph_index(&self, path: &Path) -> Result<(),io::Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let block_count = self.mir.basic_blocks().len();
+        let file_count = self.get_file_index(mir::BasicBlock::new(block_count - 1)) + 1;
+        writeln!(file, "digraph G {{")?;
+        for file_index in 0..file_count {
+            let blocks: Vec<_> = self.mir.basic_blocks().indices()
+                .filter(|&bb| self.get_file_index(bb) == file_index)
+                .collect();
+            writeln!(file, "\"graph_{}\" [ shape = \"box\" label = \"graph_{}.dot\\n{:?} ... {:?}\" ];",
+                     file_index, file_index, blocks[0], blocks[blocks.len() - 1])?;
+        }
+        let mut edges = BTreeSet::new();
+        for (bb, data) in self.mir.basic_blocks().iter_enumerated() {
+            for &successor in data.terminator().successors() {
+                let (source_file, target_file) = (self.get_file_index(bb), self.get_file_index(successor));
+                if source_file != target_file {
+                    edges.insert((source_file, target_file));
                 }
-            },
-            _ => {},
-        };
+            }
+        }
+        for (source_file, target_file) in edges {
+            writeln!(file, "\"graph_{}\" -> \"graph_{}\"", source_file, target_file)?;
+        }
+        writeln!(file, "}}")?;
+        Ok(())
+    }
 
-        let def_id = self.tcx.hir().local_def_id(node_id);
-        self.tcx.mir_borrowck(def_id);
+    /// Collect the information shown in the tables of all basic blocks.
+    fn collect_graph_info(&self) -> GraphInfo {
+        let blocks = self.mir.basic_blocks()
+            .iter_enumerated()
+            .map(|(bb, data)| {
+                let mut statements = Vec::new();
+                let mut location = mir::Location { block: bb, statement_index: 0 };
+                for statement in data.statements.iter() {
+                    if !self.is_statement_hidden(statement) {
+                        statements.push(self.collect_statement_info(location, format!("{:?}", statement)));
+                    }
+                    location.statement_index += 1;
+                }
+                let mut successors = Vec::new();
+                if let Some(ref terminator) = data.terminator {
+                    statements.push(self.collect_statement_info(location, format!("{:?}", terminator.kind)));
+                    successors = terminator.successors().map(|bb| format!("{:?}", bb)).collect();
+                }
+                BlockInfo {
+                    index: bb.index(),
+                    block: format!("{:?}", bb),
+                    initialized_before: sorted_strings(self.initialization.get_before_block(bb).iter()),
+                    statements: statements,
+                    successors: successors,
+                }
+            })
+            .collect();
+        GraphInfo {
+            function: self.function_name(),
+            blocks: blocks,
+        }
+    }
 
-        // Read Polonius facts.
-        let def_path = self.tcx.hir().def_path(def_id);
+    /// Collect the facts at the start and mid points of the location.
+    fn collect_statement_info(&self, location: mir::Location, text: String) -> StatementInfo {
+        let start_point = self.get_point(location, facts::PointType::Start);
+        let mid_point = self.get_point(location, facts::PointType::Mid);
+        let borrow_live_at = &self.polonius_info.borrowck_out_facts.borrow_live_at;
+        let in_facts = &self.polonius_info.borrowck_in_facts;
+        let loans = |point: Option<facts::PointIndex>| {
+            sorted_strings(point.and_then(|point| borrow_live_at.get(&point)).into_iter().flatten())
+        };
+        let borrow_regions = |point: Option<facts::PointIndex>| {
+            sorted_strings(in_facts.borrow_region
+                .iter()
+                .filter(|&&(_, _, borrow_point)| Some(borrow_point) == point)
+                .map(|&(region, loan, _)| (region, loan)))
+        };
+        let regions = |point: Option<facts::PointIndex>| {
+            sorted_strings(in_facts.region_live_at
+                .iter()
+                .filter(|&&(_, live_point)| Some(live_point) == point)
+                .map(|&(region, _)| region))
+        };
+        StatementInfo {
+            location: format!("{:?}", location),
+            text: text,
+            loans_start: loans(start_point),
+            loans_mid: loans(mid_point),
+            borrow_regions_start: borrow_regions(start_point),
+            borrow_regions_mid: borrow_regions(mid_point),
+            regions_start: regions(start_point),
+            regions_mid: regions(mid_point),
+            initialized_after: sorted_strings(self.initialization.get_after_statement(location).iter()),
+        }
+    }
 
-        let mir = self.tcx.mir_validated(def_id).borrow();
+    /// Write the loans alive at each program point into a JSON file. The
+    /// points are ordered by visiting the basic blocks in reverse postorder
+    /// from the start block.
+    pub fn print_timeline(&self, path: &Path) -> Result<(),io::Error> {
+        let borrow_live_at = &self.polonius_info.borrowck_out_facts.borrow_live_at;
+        let mut timeline = Vec::new();
+        for (bb, data) in mir::traversal::reverse_postorder(self.mir) {
+            for statement_index in 0..=data.statements.len() {
+                let location = mir::Location { block: bb, statement_index: statement_index };
+                for point_type in &[facts::PointType::Start, facts::PointType::Mid] {
+                    let point = facts::Point { location: location, typ: point_type.clone() };
+                    let mut loans = self.polonius_info.interner
+                        .find_point_index(&point)
+                        .and_then(|point_index| borrow_live_at.get(&point_index))
+                        .cloned()
+                        .unwrap_or_default();
+                    loans.sort();
+                    timeline.push(TimelineEntry {
+                        point: point.to_string(),
+                        loans: loans.iter().map(|loan| format!("{:?}", loan)).collect(),
+                    });
+                }
+            }
+        }
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, &timeline)?;
+        Ok(())
+    }
 
-        let graph_path = PathBuf::from("nll-facts")
-            .join(def_path.to_filename_friendly_no_crate())
-            .join("graph.dot");
-        let graph_file = File::create(graph_path).expect("Unable to create file");
-        let graph = BufWriter::new(graph_file);
+    /// Write the intervals in which the loans are alive into a separate file.
+    pub fn print_loan_intervals(&self, path: &Path) -> Result<(),io::Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        for (loan, first, last) in self.polonius_info.get_loan_intervals() {
+            writeln!(file, "{:?}: [{} ... {}]", loan, first, last)?;
+        }
+        Ok(())
+    }
 
-        let initialization = compute_definitely_initialized(&mir, self.tcx, def_path.clone());
+    /// Write a self-contained Rust file that tries to reproduce the borrow
+    /// structure of the function. The signature and the local declarations
+    /// are reconstructed from MIR and the statements are taken from the
+    /// source code of the assignments. The result is heuristic and may not
+    /// compile.
+    pub fn print_repro(&self, path: &Path) -> Result<(),io::Error> {
+        let source_map = self.tcx.sess.source_map();
+        let local_name = |local: mir::Local| {
+            match self.mir.local_decls[local].name {
+                Some(name) => name.to_string(),
+                None => format!("{:?}", local),
+            }
+        };
 
-        let mut mir_info_printer = MirInfoPrinter {
-            def_path: def_path,
-            tcx: self.tcx,
-            mir: &mir,
-            graph: cell::RefCell::new(graph),
-            initialization: initialization,
-            polonius_info: PoloniusInfo::new(self.tcx, def_id, &mir),
+        // The outermost source spans of the assignments, in source order.
+        let mut spans: Vec<(Span, Option<mir::Local>)> = Vec::new();
+        for (bb, data) in self.mir.basic_blocks().iter_enumerated() {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                if let mir::StatementKind::Assign(ref place, _) = statement.kind {
+                    let span = self.mir.source_info(mir::Location {
+                        block: bb,
+                        statement_index: statement_index,
+                    }).span;
+                    if span == self.mir.span || !self.mir.span.contains(span) {
+                        continue;
+                    }
+                    let target = match place {
+                        mir::Place::Local(local) if self.mir.local_kind(*local) ==
+                            mir::LocalKind::Var => Some(*local),
+                        _ => None,
+                    };
+                    spans.push((span, target));
+                }
+            }
+        }
+        let all_spans: Vec<_> = spans.iter().map(|&(span, _)| span).collect();
+        spans.retain(|&(span, _)| {
+            !all_spans.iter().any(|&other| other != span && other.contains(span))
+        });
+        spans.sort_by_key(|&(span, _)| span.lo());
+        spans.dedup_by_key(|&mut (span, _)| span);
+
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "// Heuristic reproduction of `{}` generated by mir-dump.",
+                 self.function_name())?;
+        writeln!(file, "// It may not compile.")?;
+        writeln!(file)?;
+        let arguments: Vec<_> = self.mir.args_iter()
+            .map(|arg| format!("{}: {}", local_name(arg), self.mir.local_decls[arg].ty))
+            .collect();
+        let return_ty = self.mir.local_decls[mir::RETURN_PLACE].ty;
+        let fn_name = if configuration::dump_redact_function_names() {
+            None
+        } else {
+            self.def_path.data.last().map(|data| data.data.as_interned_str().to_string())
         };
-        mir_info_printer.print_info().unwrap();
+        writeln!(file, "fn {}({}) -> {} {{",
+                 fn_name.unwrap_or(String::from("repro")),
+                 arguments.join(", "), return_ty)?;
+        for var in self.mir.vars_iter() {
+            let decl = &self.mir.local_decls[var];
+            let mutability = if decl.mutability == mir::Mutability::Mut { "mut " } else { "" };
+            writeln!(file, "    let {}{}: {};", mutability, local_name(var), decl.ty)?;
+        }
+        for (span, target) in spans {
+            let snippet = match source_map.span_to_snippet(span) {
+                Ok(snippet) => snippet,
+                Err(_) => continue,
+            };
+            match target {
+                Some(local) => writeln!(file, "    {} = {};", local_name(local), snippet)?,
+                None => writeln!(file, "    {};", snippet)?,
+            }
+        }
+        writeln!(file, "    unimplemented!()")?;
+        writeln!(file, "}}")?;
+        Ok(())
+    }
 
-        trace!("[visit_fn] exit");
+    /// Write the graph of the outlives constraints of the whole function.
+    /// Each region is a node labelled with the variable that has it in its
+    /// type, if any, and each constraint `'a: 'b` is an edge from `'a` to
+    /// `'b` labelled with the points at which it holds.
+    pub fn print_region_constraints(&self, path: &Path) -> Result<(),io::Error> {
+        let mut points_per_constraint: BTreeMap<_, Vec<&facts::Point>> = BTreeMap::new();
+        let mut regions = BTreeSet::new();
+        for &(region1, region2, point) in self.polonius_info.borrowck_in_facts.outlives.iter() {
+            regions.insert(region1);
+            regions.insert(region2);
+            points_per_constraint
+                .entry((region1, region2))
+                .or_default()
+                .push(self.polonius_info.interner.get_point(point));
+        }
+        let variables: HashMap<_, _> = self.polonius_info.variable_regions
+            .iter()
+            .map(|(&local, &region)| (region, local))
+            .collect();
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "digraph G {{")?;
+        writeln!(file, "label = \"Region constraints of {}\";",
+                 to_dot_string!(display_function_name(&self.def_path)))?;
+        for region in regions {
+            let label = match variables.get(&region) {
+                Some(&local) => match self.mir.local_decls[local].name {
+                    Some(name) => format!("{:?} ({})", region, name),
+                    None => format!("{:?} ({:?})", region, local),
+                },
+                None => format!("{:?}", region),
+            };
+            writeln!(file, "\"{:?}\" [ label = \"{}\" ];", region, to_dot_string!(label))?;
+        }
+        for ((region1, region2), mut points) in points_per_constraint {
+            points.sort_by_key(|point| {
+                (point.location.block, point.location.statement_index,
+                 point.typ == facts::PointType::Mid)
+            });
+            points.dedup();
+            let points = points.iter().map(|point| point.to_string()).collect();
+            writeln!(file, "\"{:?}\" -> \"{:?}\" [ label = \"{}\" ];",
+                     region1, region2, join_truncated(points))?;
+        }
+        writeln!(file, "}}")?;
+        Ok(())
     }
-}
 
-struct MirInfoPrinter<'a, 'tcx: 'a> {
-    pub def_path: hir::map::DefPath,
-    pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
-    pub mir: &'a mir::Mir<'tcx>,
-    pub graph: cell::RefCell<BufWriter<File>>,
-    pub initialization: DefinitelyInitializedAnalysisResult<'tcx>,
-    pub polonius_info: PoloniusInfo,
-}
+    /// For each basic block, write a graph of the outlives constraints
+    /// that hold at the program points of that block.
+    pub fn print_subset_per_block(&self, dir: &Path) -> Result<(),io::Error> {
+        let mut constraints_per_block = HashMap::new();
+        for &(region1, region2, point) in self.polonius_info.borrowck_in_facts.outlives.iter() {
+            let point = self.polonius_info.interner.get_point(point);
+            constraints_per_block
+                .entry(point.location.block)
+                .or_insert_with(Vec::new)
+                .push((region1, region2, point.clone()));
+        }
+        for bb in self.mir.basic_blocks().indices() {
+            let path = dir.join(format!("subset_{:?}.dot", bb));
+            let mut file = BufWriter::new(File::create(path)?);
+            writeln!(file, "digraph G {{")?;
+            writeln!(file, "label = \"{:?}\";", bb)?;
+            if let Some(constraints) = constraints_per_block.get(&bb) {
+                for (region1, region2, point) in constraints {
+                    writeln!(file, "\"{:?}\" -> \"{:?}\" [label = \"{}\"]",
+                             region1, region2, point)?;
+                }
+            }
+            writeln!(file, "}}")?;
+        }
+        Ok(())
+    }
 
-macro_rules! write_graph {
-    ( $self:ident, $( $x:expr ),* ) => {
-        writeln!($self.graph.borrow_mut(), $( $x ),*)?;
+    fn print_loans(&self) -> Result<(),io::Error> {
+        let show_intervals = configuration::dump_show_loan_intervals();
+        let show_sources = configuration::dump_show_loan_sources();
+        if !show_intervals && !show_sources {
+            return Ok(());
+        }
+        let intervals: HashMap<_, _> = self.polonius_info
+            .get_loan_intervals()
+            .into_iter()
+            .map(|(loan, first, last)| (loan, format!("{} ... {}", first, last)))
+            .collect();
+        write_graph!(self, "Loans [ style=filled shape = \"record\"");
+        write_graph!(self, "label =<<table>");
+        write_graph!(self, "<tr><td>LOANS</td></tr>");
+        write_graph!(self, "<tr>");
+        write_graph!(self, "<td>Loan</td>");
+        if show_intervals {
+            write_graph!(self, "<td>Interval</td>");
+        }
+        if show_sources {
+            write_graph!(self, "<td>Source</td>");
+        }
+        write_graph!(self, "</tr>");
+        for loan in self.polonius_info.get_loans_by_creation() {
+            write_graph!(self, "<tr>");
+            write_graph!(self, "<td>{:?}</td>", loan);
+            if show_intervals {
+                write_graph!(self, "<td>{}</td>",
+                             intervals.get(&loan).map(|s| s.as_str()).unwrap_or(""));
+            }
+            if show_sources {
+                let source = self.polonius_info.loan_spans
+                    .get(&loan)
+                    .map(|(span, snippet)| {
+                        let loc = self.tcx.sess.source_map().lookup_char_pos(span.lo());
+                        format!("{}:{}: {}",
+                                escape_html!(source_path_to_string(self.tcx, &loc.file.name)),
+                                loc.line, escape_html!(snippet))
+                    })
+                    .unwrap_or(String::from(""));
+                write_cell!(self, source);
+            }
+            write_graph!(self, "</tr>");
+        }
+        write_graph!(self, "</table>>];");
+        Ok(())
     }
-}
 
-macro_rules! to_html {
-    ( $o:expr ) => {{
-        format!("{:?}", $o)
-            .replace("{", "\\{")
-            .replace("}", "\\}")
-            .replace("&", "&amp;")
-            .replace(">", "&gt;")
-            .replace("<", "&lt;")
-            .replace("\n", "<br/>")
-    }};
-}
+    /// Print a table with one row per loan that shows where the loan was
+    /// created, its kind, the rvalue that created it, and the last point
+    /// at which it is alive.
+    fn print_loan_summary(&self) -> Result<(),io::Error> {
+        let last_live_points: HashMap<_, _> = self.polonius_info
+            .get_loan_intervals()
+            .into_iter()
+            .map(|(loan, _, last)| (loan, last))
+            .collect();
+        write_graph!(self, "LoanSummary [ shape = \"none\"");
+        write_graph!(self, "label =<<table>");
+        write_graph!(self, "<tr><td colspan=\"5\">LOAN SUMMARY</td></tr>");
+        write_graph!(self, "<tr><td>Loan</td><td>Created at</td><td>Kind</td>\
+                            <td>Source</td><td>Last alive at</td></tr>");
+        for loan in self.polonius_info.get_loans_by_creation() {
+            let created_at = self.polonius_info
+                .loan_source_location(loan)
+                .map(|location| format!("{:?}", location))
+                .unwrap_or_default();
+            let kind = if self.polonius_info.reference_moves.contains(&loan) {
+                String::from("ref-move")
+            } else if self.polonius_info.argument_moves.contains(&loan) {
+                String::from("arg-move")
+            } else {
+                self.polonius_info.loan_kinds
+                    .get(&loan)
+                    .map(|kind| escape_html!(kind.symbol()))
+                    .unwrap_or_default()
+            };
+            let source = self.loan_places
+                .get(&loan)
+                .map(|places| to_html!(places.source))
+                .unwrap_or_default();
+            let last_live = last_live_points
+                .get(&loan)
+                .map(|point| point.to_string())
+                .unwrap_or(String::from("never"));
+            write_graph!(self, "<tr><td>{:?}</td><td>{}</td><td>{}</td>",
+                         loan, created_at, kind);
+            write_cell!(self, source);
+            write_graph!(self, "<td>{}</td></tr>", last_live);
+        }
+        write_graph!(self, "</table>> ];");
+        Ok(())
+    }
 
-macro_rules! write_edge {
-    ( $self:ident, $source:ident, str $target:ident ) => {{
-        write_graph!($self, "\"{:?}\" -> \"{}\"\n", $source, stringify!($target));
-    }};
-    ( $self:ident, $source:ident, unwind $target:ident ) => {{
-        write_graph!($self, "\"{:?}\" -> \"{:?}\" [color=red]\n", $source, $target);
-    }};
-    ( $self:ident, $source:ident, imaginary $target:ident ) => {{
-        write_graph!($self, "\"{:?}\" -> \"{:?}\" [style=\"dashed\"]\n", $source, $target);
-    }};
-    ( $self:ident, $source:ident, $target:ident ) => {{
-        write_graph!($self, "\"{:?}\" -> \"{:?}\"\n", $source, $target);
-    }};
-}
+    /// Print the title node configured by `DUMP_GRAPH_TITLE` and
+    /// `DUMP_GRAPH_DESCRIPTION`. The node is also printed if Polonius
+    /// reported borrow errors.
+    fn print_title(&self) -> Result<(),io::Error> {
+        let title = configuration::dump_graph_title();
+        let description = configuration::dump_graph_description();
+        let error_count = self.polonius_info.borrowck_out_facts.errors.len();
+        if title.is_none() && description.is_none() && error_count == 0 {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        if let Some(title) = title {
+            lines.push(format!("<b>{}</b>", escape_html!(title)));
+        }
+        if let Some(description) = description {
+            lines.push(escape_html!(description));
+        }
+        if error_count > 0 {
+            lines.push(format!("<font color=\"red\">{} program point(s) with borrow errors</font>",
+                               error_count));
+        }
+        if configuration::dump_show_metadata() {
+            lines.push(format!("<font point-size=\"10\">generated at {} by mir-dump {}</font>",
+                               get_timestamp(), env!("CARGO_PKG_VERSION")));
+        }
+        write_graph!(self, "Title [ shape = \"plaintext\" label =<{}> ];",
+                     lines.join("<br/>"));
+        Ok(())
+    }
 
-macro_rules! to_sorted_string {
-    ( $o:expr ) => {{
-        let mut vector = $o.iter().map(|x| to_html!(x)).collect::<Vec<String>>();
-        vector.sort();
-        vector.join(", ")
-    }}
-}
+    /// Print the node that lists the settings with which the graph was
+    /// generated. Only the settings that differ from their defaults are
+    /// listed.
+    fn print_config_node(&self) -> Result<(),io::Error> {
+        write_graph!(self, "Config [ shape = \"note\" style = \"filled\" fillcolor = \"lightyellow\"");
+        write_graph!(self, "label =<<table border=\"0\">");
+        write_graph!(self, "<tr><td colspan=\"2\">CONFIG</td></tr>");
+        write_graph!(self, "<tr><td>mir-dump</td><td>{}</td></tr>", env!("CARGO_PKG_VERSION"));
+        write_graph!(self, "<tr><td>Polonius algorithm</td><td>{:?}</td></tr>",
+                     configuration::polonius_algorithm());
+        write_graph!(self, "<tr><td>MIR phase</td><td>{:?}</td></tr>",
+                     configuration::dump_mir_phase());
+        for (key, value) in configuration::non_default_settings() {
+            // The settings that select functions contain their names.
+            let value = if configuration::dump_redact_function_names() &&
+                    configuration::FUNCTION_NAME_SETTINGS.contains(&key.as_str()) {
+                String::from("<redacted>")
+            } else {
+                value
+            };
+            write_graph!(self, "<tr><td>{}</td><td>{}</td></tr>",
+                         escape_html!(key), escape_html!(value));
+        }
+        write_graph!(self, "</table>>];");
+        Ok(())
+    }
 
-impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
+    /// Print the node with the information about the whole function.
+    fn print_function_header(&self) -> Result<(),io::Error> {
+        let optimized = configuration::dump_mir_phase() == MirPhase::Optimized;
+        let show_borrowck_result = configuration::dump_show_borrowck_result();
+        let show_passes = configuration::dump_debug_info();
+        if !optimized && !show_borrowck_result && !show_passes {
+            return Ok(());
+        }
+        write_graph!(self, "Function [ style=filled shape = \"record\"");
+        write_graph!(self, "label =<<table>");
+        write_graph!(self, "<tr><td>FUNCTION</td></tr>");
+        if optimized {
+            write_graph!(self, "<tr><td colspan=\"2\" bgcolor=\"orange\">[OPTIMIZED MIR]</td></tr>");
+            write_graph!(self, "<tr><td colspan=\"2\">The Polonius facts are not shown</td></tr>");
+        }
+        write_graph!(self, "<tr><td>Name</td><td>{}</td></tr>",
+                     escape_html!(self.function_name()));
+        if show_borrowck_result {
+            write_graph!(self, "<tr><td>Used mutable upvars</td><td>{}</td></tr>",
+                         to_sorted_string!(self.used_mut_upvars));
+        }
+        if show_passes {
+            write_graph!(self, "<tr><td>Pass</td><td>Basic blocks</td></tr>");
+            for (pass, basic_block_count) in self.get_pass_dumps() {
+                write_graph!(self, "<tr><td>{}</td><td>{}</td></tr>",
+                             escape_html!(pass), basic_block_count);
+            }
+        }
+        write_graph!(self, "</table>>];");
+        Ok(())
+    }
 
-    pub fn print_info(&mut self) -> Result<(),io::Error> {
-        write_graph!(self, "digraph G {{\n");
-        for bb in self.mir.basic_blocks().indices() {
-            self.visit_basic_block(bb)?;
+    /// Read the MIR dumps that rustc wrote for this function with
+    /// `-Zdump-mir=all` and count the basic blocks after each pass.
+    fn get_pass_dumps(&self) -> Vec<(String, usize)> {
+        let file_prefix = format!("rustc.{}.", self.def_path.to_filename_friendly_no_crate());
+        let bb_regex = Regex::new(r"^\s*bb\d+(\[\w+\])?: \{").unwrap();
+        let mut dumps: Vec<_> = fs::read_dir("log/mir")
+            .into_iter()
+            .flat_map(|entries| entries)
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if !file_name.starts_with(&file_prefix) || !file_name.ends_with(".mir") {
+                    return None;
+                }
+                let pass = file_name[file_prefix.len()..file_name.len() - 4].to_string();
+                let content = fs::read_to_string(entry.path()).ok()?;
+                let basic_block_count = content.lines()
+                    .filter(|line| bb_regex.is_match(line))
+                    .count();
+                Some((pass, basic_block_count))
+            })
+            .collect();
+        dumps.sort();
+        dumps
+    }
+
+    /// Print a cluster containing the basic blocks of the loop, with
+    /// the clusters of the nested loops inside it.
+    fn print_loop_cluster(&self, loop_head: mir::BasicBlock) -> Result<(),io::Error> {
+        let shades = ["lightblue", "lightskyblue", "deepskyblue", "steelblue"];
+        let depth = self.loops.get_loop_depth(loop_head);
+        let index = self.loops.loop_heads.iter().position(|&head| head == loop_head).unwrap();
+        write_graph!(self, "subgraph cluster_loop_{} {{", index);
+        write_graph!(self, "label=\"Loop {} (depth {})\";", index, depth);
+        write_graph!(self, "style=filled;");
+        write_graph!(self, "bgcolor={};", shades[(depth - 1).min(shades.len() - 1)]);
+        for &nested_head in self.loops.loop_heads.iter() {
+            if self.loops.get_loop_depth(nested_head) == depth + 1 &&
+                    self.loops.loop_bodies[&loop_head].contains(&nested_head) {
+                self.print_loop_cluster(nested_head)?;
+            }
         }
-        self.print_temp_variables()?;
-        write_graph!(self, "}}\n");
+        let mut blocks: Vec<_> = self.loops.loop_bodies[&loop_head]
+            .iter()
+            .filter(|&&bb| self.loops.get_innermost_loop_head(bb) == Some(loop_head))
+            .filter(|&&bb| self.is_block_visible(bb))
+            .cloned()
+            .collect();
+        blocks.sort();
+        for bb in blocks {
+            write_graph!(self, "\"{}\";", self.node_id(bb));
+        }
+        write_graph!(self, "}}");
+        Ok(())
+    }
+
+    /// Print a comment that records where the graph came from.
+    fn print_file_header(&self) -> Result<(),io::Error> {
+        let timestamp = get_timestamp();
+        let rustc_version = option_env!("RUSTC_VERSION")
+            .or(option_env!("CFG_VERSION"))
+            .unwrap_or("unknown_version");
+        let lines = vec![
+            format!("function: {}", self.function_name()),
+            format!("crate: {}", self.tcx.crate_name(LOCAL_CRATE)),
+            format!("source: {}", source_path_to_string(
+                self.tcx, &self.tcx.sess.source_map().span_to_filename(self.mir.span))),
+            format!("timestamp: {}", timestamp),
+            format!("mir-dump version: {}", env!("CARGO_PKG_VERSION")),
+            format!("rustc version: {}", rustc_version),
+        ];
+        write_graph!(self, "/*");
+        for line in lines {
+            // Make sure that the comment is not terminated prematurely.
+            write_graph!(self, " * {}", line.replace("*/", "* /"));
+        }
+        write_graph!(self, " */");
         Ok(())
     }
 
@@ -165,43 +2142,134 @@ impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
             write_graph!(self, "label =<<table>");
             write_graph!(self, "<tr><td>VARIABLES</td></tr>");
             write_graph!(self, "<tr><td>Name</td><td>Temporary</td><td>Type</td><td>Region</td></tr>");
+            let show_all_locals = configuration::dump_show_all_locals();
+            let regions_loaded = self.polonius_info.variable_regions_loaded;
             for (temp, var) in self.mir.local_decls.iter_enumerated() {
                 let name = var.name.map(|s| s.to_string()).unwrap_or(String::from(""));
-                let region = self.polonius_info.variable_regions
-                    .get(&temp)
-                    .map(|region| format!("{:?}", region))
-                    .unwrap_or(String::from(""));
-                let typ = to_html!(var.ty);
-                write_graph!(self, "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{}</td></tr>",
-                             name, temp, typ, region);
+                let region = match self.polonius_info.variable_regions.get(&temp) {
+                    Some(region) => format!("{:?}", region),
+                    None if !regions_loaded => String::from("?"),
+                    None if show_all_locals => String::from("&lt;no region&gt;"),
+                    None => continue,
+                };
+                let mut typ = self.ty_to_html(var.ty);
+                if self.is_zst(var.ty) {
+                    typ.push_str(" [ZST]");
+                }
+                if var.ty.is_box() {
+                    typ.push_str(" [Box]");
+                }
+                write_graph!(self, "<tr><td>{}</td><td>{:?}</td>", name, temp);
+                write_cell!(self, typ, tooltip self.ty_to_text(var.ty));
+                write_graph!(self, "<td>{}</td></tr>", region);
+            }
+            let alias_groups = self.aliases.get_alias_groups();
+            if !alias_groups.is_empty() {
+                write_graph!(self, "<tr><td>ALIASES</td></tr>");
+                for group in alias_groups {
+                    let group: Vec<_> = group.iter().rev().map(|local| format!("{:?}", local)).collect();
+                    write_graph!(self, "<tr><td colspan=\"4\">{}</td></tr>", group.join(" ≡ "));
+                }
             }
             write_graph!(self, "</table>>];");
         }
         Ok(())
     }
 
+    /// Whether the Polonius facts refer to the dumped MIR. They are computed
+    /// for the validated MIR, so the columns that show them are left out
+    /// when the optimized MIR is dumped.
+    fn shows_facts(&self) -> bool {
+        configuration::dump_mir_phase() == MirPhase::Validated
+    }
+
+    fn show_start_points(&self) -> bool {
+        self.shows_facts() && configuration::dump_show_start_points()
+    }
+
+    fn show_mid_points(&self) -> bool {
+        self.shows_facts() && configuration::dump_show_mid_points()
+    }
+
+    fn show_dying_loans(&self) -> bool {
+        self.shows_facts() && configuration::dump_show_dying_loans()
+    }
+
     fn visit_basic_block(&mut self, bb: mir::BasicBlock) -> Result<(),io::Error> {
-        write_graph!(self, "\"{:?}\" [ shape = \"record\"", bb);
-        //if self.loops.loop_heads.contains(&bb) {
-            //write_graph!(self, "color=green");
-        //}
+        write_graph!(self, "\"{}\" [ shape = \"{}\"{}", self.node_id(bb), self.get_node_shape(bb),
+                     self.get_node_font_attributes());
+        if self.loops.loop_heads.contains(&bb) {
+            write_graph!(self, " color = \"green\"");
+        }
+        if configuration::dump_node_style() != NodeStyle::Record {
+            return self.visit_basic_block_plain(bb);
+        }
         write_graph!(self, "label =<<table>");
         write_graph!(self, "<th>");
-        write_graph!(self, "<td>{:?}</td>", bb);
-        write_graph!(self, "<td colspan=\"7\"></td>");
+        let point_columns = self.show_start_points() as usize +
+            self.show_mid_points() as usize;
+        let dying_columns = self.show_dying_loans() as usize;
+        write_graph!(self, "<td>{}</td>", self.block_name(bb));
+        write_graph!(self, "<td colspan=\"{}\"></td>", 1 + 3 * point_columns + dying_columns);
         write_graph!(self, "<td>Definitely Initialized</td>");
+        if self.move_data.is_some() {
+            write_graph!(self, "<td>Move Paths</td>");
+        }
+        if self.uninitialization.is_some() {
+            write_graph!(self, "<td>Definitely Uninitialized</td>");
+        }
+        if self.maybe_initialization.is_some() {
+            write_graph!(self, "<td>Maybe Initialized</td>");
+        }
         write_graph!(self, "</th>");
+        if configuration::dump_show_predecessors() {
+            let predecessors = self.predecessors
+                .get(&bb)
+                .map(|predecessors| {
+                    join_truncated(predecessors.iter().map(|bb| format!("{:?}", bb)).collect())
+                })
+                .unwrap_or_default();
+            write_graph!(self, "<tr>");
+            write_graph!(self, "<td>Predecessors</td>");
+            write_graph!(self, "<td colspan=\"{}\" align=\"left\">{}</td>",
+                         2 + 3 * point_columns + dying_columns +
+                         self.move_data.is_some() as usize +
+                         self.uninitialization.is_some() as usize +
+                         self.maybe_initialization.is_some() as usize,
+                         predecessors);
+            write_graph!(self, "</tr>");
+        }
 
         write_graph!(self, "<th>");
         if configuration::dump_show_statement_indices() {
             write_graph!(self, "<td>Nr</td>");
         }
         write_graph!(self, "<td>statement</td>");
-        write_graph!(self, "<td colspan=\"2\">Loans</td>");
-        write_graph!(self, "<td colspan=\"2\">Borrow Regions</td>");
-        write_graph!(self, "<td colspan=\"2\">Regions</td>");
-        write_graph!(self, "<td>{}</td>", self.get_definitely_initialized_before_block(bb));
+        if point_columns > 0 {
+            write_graph!(self, "<td colspan=\"{}\">Loans</td>", point_columns);
+        }
+        if dying_columns > 0 {
+            write_graph!(self, "<td>Dying Loans</td>");
+        }
+        if point_columns > 0 {
+            write_graph!(self, "<td colspan=\"{}\">Borrow Regions</td>", point_columns);
+            write_graph!(self, "<td colspan=\"{}\">Regions</td>", point_columns);
+        }
+        write_cell!(self, list self.get_definitely_initialized_before_block(bb));
+        if self.move_data.is_some() {
+            write_cell!(self, list self.move_paths_to_html(self.initialization.get_before_block(bb)));
+        }
+        if let Some(ref uninitialization) = self.uninitialization {
+            write_cell!(self, list to_sorted_list!(uninitialization.get_before_block(bb)));
+        }
+        if let Some(ref maybe_initialization) = self.maybe_initialization {
+            write_cell!(self, list to_sorted_list!(maybe_initialization.get_before_block(bb)));
+        }
         write_graph!(self, "</th>");
+        let highlight_boundaries = configuration::dump_highlight_bb_boundaries();
+        if highlight_boundaries {
+            write_graph!(self, "<hr/>");
+        }
 
         let mir::BasicBlockData { ref statements, ref terminator, .. } = self.mir[bb];
         let mut location = mir::Location { block: bb, statement_index: 0 };
@@ -228,137 +2296,906 @@ impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
                             ..
                         }
                     ),
+                    ref args,
+                    ref destination,
                     ..
                 } => {
                     // Get the unique identifier of the defintion:
                     //let def_path = self.tcx.def_path(*def_id);
                     let def_path = self.tcx.def_path_debug_str(*def_id);
-                    format!("{}<br />{}<br />{}", kind_str, to_html!(def_path), to_html!(substs))
+                    let args: Vec<_> = args
+                        .iter()
+                        .map(|arg| self.operand_to_html(location, arg))
+                        .collect();
+                    let callee = if def_id.is_local() {
+                        report_function_name(self.tcx, *def_id)
+                    } else {
+                        self.tcx.item_path_str(*def_id)
+                    };
+                    let mut call = format!("{}({})", escape_html!(callee), args.join(", "));
+                    if let Some((ref place, _)) = destination {
+                        call = format!("{} = {}", self.place_to_html(location, place), call);
+                    }
+                    if configuration::dump_mir_substs() {
+                        let mut substs = format!("{:?}", substs);
+                        let max_chars = configuration::dump_mir_substs_truncate();
+                        if max_chars > 0 && substs.chars().count() > max_chars {
+                            substs = substs.chars().take(max_chars).collect();
+                            substs.push('…');
+                        }
+                        format!("{}<br />{}<br />{}<br />{}", kind_str, to_html!(def_path),
+                                escape_html!(substs), call)
+                    } else {
+                        format!("{}<br />{}<br />{}", kind_str, to_html!(def_path), call)
+                    }
                 }
                 _ => kind_str,
             }
         } else {
             String::from("")
         };
+        let heap_allocation = terminator.as_ref().map_or(false, |term| self.is_heap_allocation(term));
+        let term_str = if heap_allocation {
+            format!("{}<br />[heap alloc]", term_str)
+        } else {
+            term_str
+        };
+        if highlight_boundaries {
+            write_graph!(self, "<hr/>");
+        }
         write_graph!(self, "<tr>");
         if configuration::dump_show_statement_indices() {
             write_graph!(self, "<td></td>");
         }
-        write_graph!(self, "<td>{}</td>", term_str);
-        write_graph!(self, "<td></td>");
-        self.write_mid_point_blas(location)?;
-        write_graph!(self, "<td colspan=\"4\"></td>");
-            write_graph!(self, "<td>{}</td>",
-                         self.get_definitely_initialized_after_statement(location));
+        if terminator.as_ref().map_or(false, |term| self.is_unused_must_use_call(term)) {
+            write_cell!(self, term_str, "bgcolor=\"yellow\"");
+        } else if heap_allocation {
+            write_cell!(self, term_str, "bgcolor=\"lightblue\"");
+        } else {
+            write_cell!(self, term_str);
+        }
+        if self.show_start_points() {
+            write_graph!(self, "<td></td>");
+        }
+        if self.show_mid_points() {
+            self.write_mid_point_blas(location)?;
+        }
+        if self.show_dying_loans() {
+            self.write_dying_loans(location)?;
+        }
+        if point_columns > 0 {
+            write_graph!(self, "<td colspan=\"{}\"></td>", 2 * point_columns);
+        }
+            write_cell!(self, list
+                        self.get_definitely_initialized_after_statement(location));
+        if self.move_data.is_some() {
+            write_cell!(self, list
+                        self.move_paths_to_html(self.initialization.get_after_statement(location)));
+        }
+        self.write_definitely_uninitialized_after_statement(location)?;
+        self.write_maybe_initialized_after_statement(location)?;
         write_graph!(self, "</tr>");
         write_graph!(self, "</table>> ];");
 
         if let Some(ref terminator) = &terminator {
             self.visit_terminator(bb, terminator)?;
         }
+        self.visit_collapsed_terminators(bb)?;
+
+        Ok(())
+    }
+
+    /// The shape of the node that represents the basic block.
+    fn get_node_shape(&self, bb: mir::BasicBlock) -> &'static str {
+        if configuration::dump_switch_diamond() {
+            if let Some(ref terminator) = self.mir[bb].terminator {
+                if let mir::TerminatorKind::SwitchInt { .. } = terminator.kind {
+                    return "diamond";
+                }
+            }
+        }
+        match configuration::dump_node_style() {
+            NodeStyle::Record => "record",
+            NodeStyle::Box => "box",
+            NodeStyle::Ellipse => "ellipse",
+        }
+    }
+
+    /// Print the basic block as a plain text label that lists its
+    /// statements and terminator, one per line.
+    fn visit_basic_block_plain(&self, bb: mir::BasicBlock) -> Result<(),io::Error> {
+        let mir::BasicBlockData { ref statements, ref terminator, .. } = self.mir[bb];
+        let mut lines = vec![self.block_name(bb)];
+        for statement in statements {
+            if !self.is_statement_hidden(statement) {
+                lines.push(format!("{:?}", statement));
+            }
+        }
+        if let Some(ref terminator) = terminator {
+            lines.push(format!("{:?}", terminator.kind));
+        }
+        let label: String = lines
+            .iter()
+            .map(|line| format!("{}\\l", to_dot_string!(line)))
+            .collect();
+        write_graph!(self, "label = \"{}\" ];", label);
 
+        if let Some(ref terminator) = terminator {
+            self.visit_terminator(bb, terminator)?;
+        }
+        self.visit_collapsed_terminators(bb)?;
         Ok(())
     }
 
+    /// Check whether the terminator is a call of `Box::new` or of the
+    /// `exchange_malloc` lang item, which allocate on the heap.
+    fn is_heap_allocation(&self, terminator: &mir::Terminator) -> bool {
+        if let mir::TerminatorKind::Call {
+            func: mir::Operand::Constant(box mir::Constant {
+                literal: ty::Const {
+                    ty: ty::TyS { sty: ty::TyKind::FnDef(def_id, _), .. },
+                    ..
+                },
+                ..
+            }),
+            ..
+        } = terminator.kind
+        {
+            let lang_items = self.tcx.lang_items();
+            if Some(*def_id) == lang_items.exchange_malloc_fn() {
+                return true;
+            }
+            if let Some(impl_def_id) = self.tcx.impl_of_method(*def_id) {
+                if let Some(adt_def) = self.tcx.type_of(impl_def_id).ty_adt_def() {
+                    return Some(adt_def.did) == lang_items.owned_box() &&
+                        self.tcx.item_name(*def_id).to_string() == "new";
+                }
+            }
+        }
+        false
+    }
+
+    /// Check whether the terminator is a call of a `#[must_use]` function
+    /// whose result is stored in a temporary that is never read.
+    fn is_unused_must_use_call(&self, terminator: &mir::Terminator) -> bool {
+        if let mir::TerminatorKind::Call {
+            func: mir::Operand::Constant(box mir::Constant {
+                literal: ty::Const {
+                    ty: ty::TyS { sty: ty::TyKind::FnDef(def_id, _), .. },
+                    ..
+                },
+                ..
+            }),
+            destination: Some((mir::Place::Local(local), _)),
+            ..
+        } = terminator.kind
+        {
+            if !self.tcx.has_attr(*def_id, "must_use") {
+                return false;
+            }
+            if self.mir.local_kind(local) != mir::LocalKind::Temp {
+                return false;
+            }
+            !self.read_locals.contains(&local)
+        } else {
+            false
+        }
+    }
+
+    /// Should the statement be omitted from the graph?
+    fn is_statement_hidden(&self, statement: &mir::Statement) -> bool {
+        match statement.kind {
+            mir::StatementKind::AscribeUserType(..) => {
+                configuration::dump_hide_ascribe_user_type()
+            }
+            mir::StatementKind::StorageLive(..) |
+            mir::StatementKind::StorageDead(..) => {
+                configuration::dump_storage_display() == StorageDisplay::Hide
+            }
+            _ => false,
+        }
+    }
+
+    /// Is the statement shown as a scope hint?
+    fn is_storage_hint(&self, statement: &mir::Statement) -> bool {
+        match statement.kind {
+            mir::StatementKind::StorageLive(..) |
+            mir::StatementKind::StorageDead(..) => {
+                configuration::dump_storage_display() == StorageDisplay::Hint
+            }
+            _ => false,
+        }
+    }
+
     fn visit_statement(&self, location: mir::Location,
-                       statement: &mir::Statement) -> Result<(),io::Error> {
+                       statement: &mir::Statement<'tcx>) -> Result<(),io::Error> {
+        if self.is_statement_hidden(statement) {
+            return Ok(());
+        }
+
         write_graph!(self, "<tr>");
         if configuration::dump_show_statement_indices() {
             write_graph!(self, "<td>{}</td>", location.statement_index);
         }
-        write_graph!(self, "<td>{}</td>", to_html!(statement));
-
         let start_point = self.get_point(location, facts::PointType::Start);
         let mid_point = self.get_point(location, facts::PointType::Mid);
 
-        // Loans.
-        if let Some(ref blas) = self.polonius_info.borrowck_out_facts.borrow_live_at.get(&start_point).as_ref() {
-            write_graph!(self, "<td>{}</td>", to_sorted_string!(blas));
+        let mut statement_html = self.statement_to_html(location, statement);
+        let errors = &self.polonius_info.borrowck_out_facts.errors;
+        let mut error_loans: Vec<_> = start_point.and_then(|point| errors.get(&point)).into_iter()
+            .chain(mid_point.and_then(|point| errors.get(&point)))
+            .flat_map(|loans| loans.iter().cloned())
+            .collect();
+        if !error_loans.is_empty() {
+            error_loans.sort();
+            error_loans.dedup();
+            // The marker comes first, so that truncating a long statement
+            // does not remove it.
+            statement_html = format!("<font color=\"red\"><b>[BORROW ERROR: {}]</b></font><br/>{}",
+                                     self.loans_to_html(&error_loans), statement_html);
+        }
+        let use_after_drop = self.uses_after_drop
+            .iter()
+            .find(|use_after_drop| use_after_drop.move_location == location);
+        if let Some(use_after_drop) = use_after_drop {
+            statement_html = format!("{}<br/>[moved after drop at {:?}]",
+                                     statement_html, use_after_drop.drop_location);
+        }
+        let raw_pointer_use = if configuration::dump_highlight_raw_casts() {
+            self.find_raw_pointer_use(statement)
         } else {
-            write_graph!(self, "<td></td>");
+            None
+        };
+        if let Some((ref annotation, _)) = raw_pointer_use {
+            statement_html = format!("{}<br/>{}", statement_html, annotation);
+        }
+        let statement_text = html_to_plain_text(&statement_html);
+        if use_after_drop.is_some() {
+            write_cell!(self, statement_html, "bgcolor=\"red\"", tooltip statement_text);
+        } else if !error_loans.is_empty() {
+            write_cell!(self, statement_html, "bgcolor=\"salmon\"", tooltip statement_text);
+        } else if let Some((_, color)) = raw_pointer_use {
+            write_cell!(self, statement_html, &format!("bgcolor=\"{}\"", color), tooltip statement_text);
+        } else if self.is_storage_hint(statement) {
+            write_cell!(self, statement_html, "bgcolor=\"lightgrey\"", tooltip statement_text);
+        } else {
+            write_cell!(self, statement_html, tooltip statement_text);
+        }
+
+        let mut points = Vec::new();
+        if self.show_start_points() {
+            points.push(start_point);
+        }
+        if self.show_mid_points() {
+            points.push(mid_point);
+        }
+
+        // Loans.
+        if self.show_start_points() {
+            let borrow_live_at = &self.polonius_info.borrowck_out_facts.borrow_live_at;
+            if let Some(blas) = start_point.and_then(|point| borrow_live_at.get(&point)) {
+                write_cell!(self, self.loans_to_html(blas), tooltip self.loans_tooltip(blas));
+            } else {
+                write_graph!(self, "<td></td>");
+            }
+        }
+        if self.show_mid_points() {
+            self.write_mid_point_blas(location)?;
+        }
+        if self.show_dying_loans() {
+            self.write_dying_loans(location)?;
         }
-        self.write_mid_point_blas(location)?;
 
         // Borrow regions (loan start points).
-        let borrow_regions: Vec<_> = self.polonius_info.borrowck_in_facts
-            .borrow_region
-            .iter()
-            .filter(|(_, _, point)| *point == start_point)
-            .cloned()
-            .map(|(region, loan, _)| (region, loan))
-            .collect();
-        write_graph!(self, "<td>{}</td>", to_sorted_string!(borrow_regions));
-        let borrow_regions: Vec<_> = self.polonius_info.borrowck_in_facts
-            .borrow_region
-            .iter()
-            .filter(|(_, _, point)| *point == mid_point)
-            .cloned()
-            .map(|(region, loan, _)| (region, loan))
-            .collect();
-        write_graph!(self, "<td>{}</td>", to_sorted_string!(borrow_regions));
+        for &point in points.iter() {
+            let borrow_regions: Vec<_> = self.polonius_info.borrowck_in_facts
+                .borrow_region
+                .iter()
+                .filter(|(_, _, borrow_point)| Some(*borrow_point) == point)
+                .cloned()
+                .map(|(region, loan, _)| (region, loan))
+                .collect();
+            let tooltip = self.regions_tooltip(borrow_regions.iter().map(|&(region, _)| region));
+            write_cell!(self, self.borrow_regions_to_html(&borrow_regions), tooltip tooltip);
+        }
 
         // Regions alive at this program point.
-        let regions: Vec<_> = self.polonius_info.borrowck_in_facts
-            .region_live_at
-            .iter()
-            .filter(|(_, point)| *point == start_point)
-            .cloned()
-            // TODO: Understand why we cannot unwrap here:
-            .map(|(region, _)| (region, self.polonius_info.find_variable(region)))
-            .collect();
-        write_graph!(self, "<td>{}</td>", to_sorted_string!(regions));
-        let regions: Vec<_> = self.polonius_info.borrowck_in_facts
-            .region_live_at
-            .iter()
-            .filter(|(_, point)| *point == mid_point)
-            .cloned()
-            // TODO: Understand why we cannot unwrap here:
-            .map(|(region, _)| (region, self.polonius_info.find_variable(region)))
-            .collect();
-        write_graph!(self, "<td>{}</td>", to_sorted_string!(regions));
+        for &point in points.iter() {
+            let regions: Vec<_> = self.polonius_info.borrowck_in_facts
+                .region_live_at
+                .iter()
+                .filter(|(_, live_point)| Some(*live_point) == point)
+                .cloned()
+                // TODO: Understand why we cannot unwrap here:
+                .map(|(region, _)| (region, self.polonius_info.find_variable(region)))
+                .collect();
+            let tooltip = self.regions_tooltip(regions.iter().map(|&(region, _)| region));
+            write_cell!(self, self.regions_to_html(&regions), tooltip tooltip);
+        }
 
-        write_graph!(self, "<td>{}</td>",
-                     self.get_definitely_initialized_after_statement(location));
+        write_cell!(self, list
+                    self.get_definitely_initialized_after_statement(location));
+        if self.move_data.is_some() {
+            write_cell!(self, list
+                        self.move_paths_to_html(self.initialization.get_after_statement(location)));
+        }
+        self.write_definitely_uninitialized_after_statement(location)?;
+        self.write_maybe_initialized_after_statement(location)?;
 
         write_graph!(self, "</tr>");
         Ok(())
     }
 
-    fn get_point(&self, location: mir::Location, point_type: facts::PointType) -> facts::PointIndex {
+    /// Check whether the statement casts a value to a raw pointer or borrows
+    /// the target of a raw pointer. Returns the annotation and the
+    /// background color of the statement. This compiler version has no cast
+    /// from a raw pointer to a reference; such conversions are written as
+    /// `&*ptr`.
+    fn find_raw_pointer_use(&self, statement: &mir::Statement<'tcx>) -> Option<(String, &'static str)> {
+        let rvalue = match statement.kind {
+            mir::StatementKind::Assign(_, ref rvalue) => rvalue,
+            _ => return None,
+        };
+        match **rvalue {
+            mir::Rvalue::Cast(mir::CastKind::Misc, _, ty) if ty.is_unsafe_ptr() => {
+                Some((format!("[→{}]", self.ty_to_html(ty)), "orange"))
+            }
+            mir::Rvalue::Ref(_, _, mir::Place::Projection(box mir::Projection {
+                ref base,
+                elem: mir::ProjectionElem::Deref,
+            })) => {
+                let base_ty = base.ty(self.mir, self.tcx).to_ty(self.tcx);
+                if base_ty.is_unsafe_ptr() {
+                    Some((format!("[{}→&amp;T UNSAFE]", self.ty_to_html(base_ty)), "red"))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Format the statement, using a more readable notation for the
+    /// statement kinds that have one.
+    fn statement_to_html(&self, location: mir::Location,
+                         statement: &mir::Statement<'tcx>) -> String {
+        match statement.kind {
+            mir::StatementKind::AscribeUserType(ref place, variance, ref user_ty) => {
+                let arrow = match variance {
+                    ty::Variance::Covariant => "↑",
+                    ty::Variance::Contravariant => "↓",
+                    ty::Variance::Invariant => "↕",
+                    ty::Variance::Bivariant => "∗",
+                };
+                format!("{} {} {}", to_html!(place), arrow, to_html!(user_ty))
+            }
+            mir::StatementKind::Assign(ref place, ref rvalue) => {
+                let assignment = format!("{} = {}", self.place_to_html(location, place),
+                                         self.rvalue_to_html(location, rvalue));
+                if self.is_zst(place.ty(self.mir, self.tcx).to_ty(self.tcx)) {
+                    format!("{} <font color=\"gray\">[no-op]</font>", assignment)
+                } else {
+                    assignment
+                }
+            }
+            mir::StatementKind::StorageLive(local)
+                    if configuration::dump_storage_display() == StorageDisplay::Hint => {
+                format!("<font point-size=\"10\">▶ {:?} enters scope</font>", local)
+            }
+            mir::StatementKind::StorageDead(local)
+                    if configuration::dump_storage_display() == StorageDisplay::Hint => {
+                format!("<font point-size=\"10\">◀ {:?} leaves scope</font>", local)
+            }
+            _ => to_html!(statement),
+        }
+    }
+
+    /// Format the rvalue, making it visible which operands are copied and
+    /// which are moved.
+    fn rvalue_to_html(&self, location: mir::Location, rvalue: &mir::Rvalue<'tcx>) -> String {
+        match rvalue {
+            mir::Rvalue::Use(ref operand) => self.operand_to_html(location, operand),
+            mir::Rvalue::Repeat(ref operand, count) => {
+                format!("[{}; {:?}]", self.operand_to_html(location, operand), count)
+            }
+            mir::Rvalue::Cast(ref kind, ref operand, ty) => {
+                format!("{} as {} ({:?})", self.operand_to_html(location, operand), self.ty_to_html(*ty), kind)
+            }
+            mir::Rvalue::BinaryOp(ref op, ref left, ref right) => {
+                format!("{:?}({}, {})", op,
+                        self.operand_to_html(location, left), self.operand_to_html(location, right))
+            }
+            mir::Rvalue::CheckedBinaryOp(ref op, ref left, ref right) => {
+                format!("Checked{:?}({}, {})", op,
+                        self.operand_to_html(location, left), self.operand_to_html(location, right))
+            }
+            mir::Rvalue::UnaryOp(ref op, ref operand) => {
+                format!("{:?}({})", op, self.operand_to_html(location, operand))
+            }
+            _ => to_html!(rvalue),
+        }
+    }
+
+    /// Format the place. If the place dereferences a local that aliases
+    /// another local, the dereferenced local is replaced with the original.
+    /// Projections are annotated with their type.
+    fn place_to_html(&self, location: mir::Location, place: &mir::Place<'tcx>) -> String {
+        let place_html = match self.aliases.get_before_statement(location) {
+            Some(aliases) => to_html!(substitute_aliases(place, aliases)),
+            None => to_html!(place),
+        };
+        match place {
+            mir::Place::Projection(_) => {
+                let ty = get_place_type(place, self.mir, self.tcx);
+                format!("{}: {}", place_html, self.ty_to_html(ty))
+            }
+            _ => place_html,
+        }
+    }
+
+    /// The name of the function as it should be displayed. If
+    /// `DUMP_REDACT_FUNCTION_NAMES` is set, the name is replaced with its hash.
+    fn function_name(&self) -> String {
+        display_function_name(&self.def_path)
+    }
+
+    /// The signature of the function reconstructed from MIR, for example
+    /// `fn foo(a: T) -> ()`. Unnamed arguments are shown as their locals.
+    fn function_signature(&self) -> String {
+        let arguments: Vec<_> = self.mir.args_iter()
+            .map(|arg| {
+                let name = match self.mir.local_decls[arg].name {
+                    Some(name) => name.to_string(),
+                    None => format!("{:?}", arg),
+                };
+                format!("{}: {}", name, self.mir.local_decls[arg].ty)
+            })
+            .collect();
+        format!("fn {}({}) -> {}", self.function_name(), arguments.join(", "),
+                self.mir.local_decls[mir::RETURN_PLACE].ty)
+    }
+
+    /// The font attributes of the basic block nodes, each preceded by a space.
+    fn get_node_font_attributes(&self) -> String {
+        let mut attributes = String::new();
+        let fontname = configuration::dump_node_fontname();
+        if !fontname.is_empty() {
+            attributes.push_str(&format!(" fontname = \"{}\"", to_dot_string!(fontname)));
+        }
+        let fontsize = configuration::dump_node_fontsize();
+        if !fontsize.is_empty() {
+            attributes.push_str(&format!(" fontsize = \"{}\"", to_dot_string!(fontsize)));
+        }
+        attributes
+    }
+
+    /// The kind of a normal edge: `back` if it closes a loop, that is, if
+    /// its target dominates its source.
+    fn edge_kind<B: Borrow<mir::BasicBlock>>(&self, source: mir::BasicBlock, target: B) -> &'static str {
+        if self.loops.back_edges.contains(&(source, *target.borrow())) {
+            "back"
+        } else {
+            "normal"
+        }
+    }
+
+    /// The attribute list that should be appended to the edges of the
+    /// given kind.
+    fn edge_attributes(&self, kind: &str, label: Option<String>) -> String {
+        let mut attributes = Vec::new();
+        match self.edge_styles.get(kind) {
+            Some(style) if !style.is_empty() => attributes.push(style.clone()),
+            _ => {}
+        }
+        if let Some(label) = label {
+            attributes.push(format!("label=\"{}\"", to_dot_string!(label)));
+        }
+        if attributes.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attributes.join(","))
+        }
+    }
+
+    /// The condition under which the terminator transfers the control to
+    /// its successor with the given index (in the order of
+    /// `Terminator::successors`). `None` if edge labels are disabled or the
+    /// edge has no interesting condition.
+    fn edge_label(&self, terminator: &mir::Terminator<'tcx>, index: usize) -> Option<String> {
+        if !configuration::dump_show_edge_labels() {
+            return None;
+        }
+        match terminator.kind {
+            mir::TerminatorKind::SwitchInt { ref values, switch_ty, .. } => {
+                Some(match values.get(index) {
+                    Some(&value) if switch_ty.is_bool() => {
+                        String::from(if value == 0 { "false" } else { "true" })
+                    }
+                    Some(&value) => self.format_switch_value(value, switch_ty),
+                    None => String::from("otherwise"),
+                })
+            }
+            mir::TerminatorKind::FalseEdges { .. } => {
+                Some(String::from(if index == 0 { "real" } else { "imaginary" }))
+            }
+            mir::TerminatorKind::Assert { .. } => {
+                Some(String::from(if index == 0 { "ok" } else { "panic" }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Format the raw bits of a `SwitchInt` value as a value of
+    /// `switch_ty`, for example `-1` instead of `4294967295` for `i32`.
+    fn format_switch_value(&self, value: u128, switch_ty: ty::Ty<'tcx>) -> String {
+        match switch_ty.sty {
+            ty::Int(_) => {
+                match self.tcx.layout_of(self.param_env.and(switch_ty)) {
+                    Ok(layout) => {
+                        let shift = 128 - layout.size.bits();
+                        (((value << shift) as i128) >> shift).to_string()
+                    }
+                    Err(_) => value.to_string(),
+                }
+            }
+            ty::Char => match std::char::from_u32(value as u32) {
+                Some(character) => format!("{:?}", character),
+                None => value.to_string(),
+            },
+            _ => value.to_string(),
+        }
+    }
+
+    /// Is the type zero-sized? Assignments to zero-sized places have no
+    /// effect at runtime.
+    fn is_zst(&self, ty: ty::Ty<'tcx>) -> bool {
+        self.tcx.layout_of(self.param_env.and(ty)).map_or(false, |layout| layout.is_zst())
+    }
+
+    /// Format the type with the precision set by `DUMP_TYPE_PRECISION`
+    /// and the depth limit set by `DUMP_MAX_TYPE_DEPTH`.
+    fn ty_to_html(&self, ty: ty::Ty<'tcx>) -> String {
+        let text = self.ty_to_text(ty);
+        escape_html!(truncate_type_depth(&text, configuration::dump_max_type_depth()))
+    }
+
+    /// Format the type with the precision set by `DUMP_TYPE_PRECISION`.
+    fn ty_to_text(&self, ty: ty::Ty<'tcx>) -> String {
+        let full = if configuration::dump_pretty_types() {
+            pretty_ty(ty)
+        } else {
+            format!("{:?}", ty)
+        };
+        let typ = match configuration::dump_type_precision() {
+            TypePrecision::Full => full,
+            TypePrecision::Short => {
+                let mut crate_names: HashSet<String> = self.tcx
+                    .crates()
+                    .iter()
+                    .map(|&krate| self.tcx.crate_name(krate).to_string())
+                    .collect();
+                crate_names.insert(self.tcx.crate_name(LOCAL_CRATE).to_string());
+                PATH_REGEX.replace_all(&full, |caps: &regex::Captures| {
+                    let path = &caps[0];
+                    match path.find("::") {
+                        Some(end) if crate_names.contains(&path[..end]) => {
+                            path[end + 2..].to_string()
+                        }
+                        _ => path.to_string(),
+                    }
+                }).to_string()
+            }
+            TypePrecision::Abbrev => {
+                PATH_REGEX.replace_all(&full, |caps: &regex::Captures| {
+                    let segments: Vec<_> = caps[0].split("::").collect();
+                    segments[segments.len() - 2..].join("::")
+                }).to_string()
+            }
+        };
+        typ
+    }
+
+    /// Format the operand as `copy place`, `move place`, or the value of
+    /// the constant.
+    fn operand_to_html(&self, location: mir::Location, operand: &mir::Operand<'tcx>) -> String {
+        match operand {
+            mir::Operand::Copy(ref place) => {
+                format!("<font color=\"blue\">copy</font> {}", self.place_to_html(location, place))
+            }
+            mir::Operand::Move(ref place) => {
+                format!("<font color=\"red\">move</font> {}", self.place_to_html(location, place))
+            }
+            mir::Operand::Constant(ref constant) => {
+                escape_html!(format!("{:?}", constant).trim_start_matches("const "))
+            }
+        }
+    }
+
+    /// The index of the point, or `None` if the point does not appear in
+    /// the loaded facts, for example because `DUMP_LOAD_CFG_EDGE` is off.
+    fn get_point(&self, location: mir::Location, point_type: facts::PointType) -> Option<facts::PointIndex> {
         let point = facts::Point {
             location: location,
             typ: point_type,
         };
-        self.polonius_info.interner.get_point_index(&point)
+        self.polonius_info.interner.find_point_index(&point)
     }
 
-    /// Print the HTML cell with loans at given location.
+    /// Print the HTML cell with loans at given location. If the cell
+    /// contains conflicting loans, it is red and the tooltip lists them.
     fn write_mid_point_blas(&self, location: mir::Location) -> Result<(),io::Error> {
         let mid_point = self.get_point(location, facts::PointType::Mid);
         let borrow_live_at_map = &self.polonius_info.borrowck_out_facts.borrow_live_at;
-        let mut blas = if let Some(ref blas) = borrow_live_at_map.get(&mid_point).as_ref() {
-            (**blas).clone()
-        } else {
-            Vec::new()
-        };
+        let mut blas = mid_point
+            .and_then(|point| borrow_live_at_map.get(&point))
+            .cloned()
+            .unwrap_or_default();
 
         // Format the loans and mark the dying ones.
         blas.sort();
 
-        write_graph!(self, "<td>{}</td>", to_sorted_string!(blas));
+        match mid_point.and_then(|point| self.conflicting_loans.get(&point)) {
+            Some(conflicts) => {
+                let mut tooltip = self.loans_tooltip(&blas);
+                for &(first, second) in conflicts {
+                    tooltip.push_str(&format!("\nconflict: {:?} and {:?}", first, second));
+                }
+                write_cell!(self, self.loans_to_html(&blas), "bgcolor=\"red\"", tooltip tooltip);
+            }
+            None => {
+                write_cell!(self, self.loans_to_html(&blas), tooltip self.loans_tooltip(&blas));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The loans that die at the statement: the loans that are alive at its
+    /// start point but not at its mid point, and the loans that are alive
+    /// at its mid point but not at the start point of any successor.
+    fn compute_dying_loans(&self, location: mir::Location) -> Vec<facts::Loan> {
+        let borrow_live_at = &self.polonius_info.borrowck_out_facts.borrow_live_at;
+        let loans_at = |point: Option<facts::PointIndex>| -> HashSet<facts::Loan> {
+            point
+                .and_then(|point| borrow_live_at.get(&point))
+                .map(|loans| loans.iter().cloned().collect())
+                .unwrap_or_default()
+        };
+        let mid_point = self.get_point(location, facts::PointType::Mid);
+        let start_loans = loans_at(self.get_point(location, facts::PointType::Start));
+        let mid_loans = loans_at(mid_point);
+        let successor_loans: Vec<_> = self.polonius_info.borrowck_in_facts
+            .cfg_edge
+            .iter()
+            .filter(|&&(source, _)| Some(source) == mid_point)
+            .map(|&(_, target)| loans_at(Some(target)))
+            .collect();
+        let mut dying_loans: Vec<_> = start_loans
+            .difference(&mid_loans)
+            .chain(mid_loans.iter().filter(|loan| {
+                !successor_loans.iter().any(|loans| loans.contains(*loan))
+            }))
+            .cloned()
+            .collect();
+        dying_loans.sort();
+        dying_loans.dedup();
+        dying_loans
+    }
 
+    /// Print the HTML cell with the loans that die at the given location.
+    fn write_dying_loans(&self, location: mir::Location) -> Result<(),io::Error> {
+        let dying_loans = self.compute_dying_loans(location);
+        let items: Vec<_> = dying_loans
+            .iter()
+            .map(|&loan| format!("<s>{}</s>", self.loan_to_html(loan)))
+            .collect();
+        write_cell!(self, join_truncated(items), tooltip self.loans_tooltip(&dying_loans));
         Ok(())
     }
 
-    fn visit_terminator(&self, bb: mir::BasicBlock, terminator: &mir::Terminator) -> Result<(),io::Error> {
+    /// Format the region. If `DUMP_EXPAND_REGION_NAMES` is set, the region
+    /// is followed by the variable that has it in its type, for example
+    /// `R5 (x)`.
+    fn region_to_html(&self, region: facts::Region) -> String {
+        if !configuration::dump_expand_region_names() ||
+                !self.polonius_info.variable_regions_loaded {
+            return to_html!(region);
+        }
+        match self.polonius_info.find_variable(region) {
+            Some(local) => {
+                match self.mir.local_decls[local].name {
+                    Some(name) => format!("{:?} ({})", region, escape_html!(name.to_string())),
+                    None => format!("{:?} ({:?})", region, local),
+                }
+            }
+            None => to_html!(region),
+        }
+    }
+
+    /// Format the regions created by loans.
+    fn borrow_regions_to_html(&self, borrow_regions: &[(facts::Region, facts::Loan)]) -> String {
+        if !configuration::dump_expand_region_names() {
+            return to_sorted_string!(borrow_regions);
+        }
+        let mut items: Vec<_> = borrow_regions
+            .iter()
+            .map(|&(region, loan)| format!("({}, {:?})", self.region_to_html(region), loan))
+            .collect();
+        items.sort();
+        join_truncated(items)
+    }
+
+    /// Format the live regions with the variables that have them in their
+    /// types. Regions that are live at fewer points than
+    /// `DUMP_MIN_REGION_IMPORTANCE` are only counted. At most
+    /// `DUMP_MAX_REGIONS_PER_CELL` regions are shown, universal regions
+    /// first, followed by the regions with the most variables.
+    fn regions_to_html(&self, regions: &[(facts::Region, Option<mir::Local>)]) -> String {
+        let min_importance = configuration::dump_min_region_importance();
+        let (mut regions, minor_regions): (Vec<_>, Vec<_>) = regions
+            .iter()
+            .partition(|&&(region, _)| {
+                self.region_importance.get(&region).cloned().unwrap_or(0) >= min_importance
+            });
+        let universal_regions: HashSet<_> = self.polonius_info.borrowck_in_facts
+            .universal_region
+            .iter()
+            .cloned()
+            .collect();
+        if configuration::dump_hide_universal_regions() {
+            regions.retain(|&&(region, _)| !universal_regions.contains(&region));
+        }
+        let max_regions = configuration::dump_max_regions_per_cell();
+        let mut hidden_count = 0;
+        if max_regions > 0 && regions.len() > max_regions {
+            regions.sort_by_key(|&&(region, _)| {
+                let variable_count = self.polonius_info.variable_regions
+                    .values()
+                    .filter(|&&variable_region| variable_region == region)
+                    .count();
+                (!universal_regions.contains(&region), cmp::Reverse(variable_count), region)
+            });
+            hidden_count = regions.len() - max_regions;
+            regions.truncate(max_regions);
+        }
+        let format_regions = |regions: &[&(facts::Region, Option<mir::Local>)]| {
+            if configuration::dump_expand_region_names() {
+                let mut items: Vec<_> = regions
+                    .iter()
+                    .map(|&&(region, _)| self.region_to_html(region))
+                    .collect();
+                items.sort();
+                join_truncated(items)
+            } else {
+                to_sorted_string!(regions)
+            }
+        };
+        let mut html = if configuration::dump_show_region_kinds() {
+            let (universal, local): (Vec<_>, Vec<_>) = regions
+                .iter()
+                .cloned()
+                .partition(|&&(region, _)| universal_regions.contains(&region));
+            let mut groups = Vec::new();
+            if !universal.is_empty() {
+                groups.push(format!("[Universal: {}]", format_regions(&universal)));
+            }
+            if !local.is_empty() {
+                groups.push(format!("[Local: {}]", format_regions(&local)));
+            }
+            groups.join("<br/>")
+        } else {
+            format_regions(&regions)
+        };
+        if hidden_count > 0 {
+            html.push_str(&format!(" (+{} more)", hidden_count));
+        }
+        if !minor_regions.is_empty() {
+            html.push_str(&format!(" (+{} minor regions)", minor_regions.len()));
+        }
+        html
+    }
+
+    /// A tooltip that lists the loans with the locations, the source code,
+    /// the source positions, and the MIR assignments that created them.
+    fn loans_tooltip(&self, loans: &[facts::Loan]) -> String {
+        let mut loans = loans.to_vec();
+        loans.sort();
+        loans.dedup();
+        let lines: Vec<_> = loans
+            .iter()
+            .map(|loan| {
+                let created_at = self.polonius_info
+                    .loan_source_location(*loan)
+                    .map(|location| format!("{:?}", location))
+                    .unwrap_or(String::from("unknown point"));
+                let source = self.polonius_info.loan_spans
+                    .get(loan)
+                    .map(|(span, snippet)| {
+                        let loc = self.tcx.sess.source_map().lookup_char_pos(span.lo());
+                        format!(" `{}` ({}:{}:{})", snippet,
+                                source_path_to_string(self.tcx, &loc.file.name),
+                                loc.line, loc.col.0 + 1)
+                    })
+                    .unwrap_or(String::from(""));
+                let assignment = self.loan_places
+                    .get(loan)
+                    .map(|places| format!(": {:?} = {:?}", places.dest, places.source))
+                    .unwrap_or(String::from(""));
+                format!("{:?}: created at {}{}{}", loan, created_at, source, assignment)
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    /// A tooltip that lists the regions with the variables that have them
+    /// in their types.
+    fn regions_tooltip<I: Iterator<Item = facts::Region>>(&self, regions: I) -> String {
+        let mut regions: Vec<_> = regions.collect();
+        regions.sort();
+        regions.dedup();
+        let lines: Vec<_> = regions
+            .into_iter()
+            .map(|region| {
+                match self.polonius_info.find_variable(region) {
+                    Some(local) => {
+                        match self.mir.local_decls[local].name {
+                            Some(name) => format!("{:?}: {:?} ({})", region, local, name),
+                            None => format!("{:?}: {:?}", region, local),
+                        }
+                    }
+                    None => format!("{:?}: no variable", region),
+                }
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    /// Format the loan, followed by the location that created it if
+    /// `DUMP_SHOW_LOAN_LOCATIONS` is set and preceded by its kind if
+    /// `DUMP_SHOW_LOAN_KINDS` is set, for example `&amp;mut L3@bb1[2]`.
+    fn loan_to_html(&self, loan: facts::Loan) -> String {
+        let text = match self.polonius_info.loan_source_location(loan) {
+            Some(location) if configuration::dump_show_loan_locations() => {
+                format!("{}@{:?}", to_html!(loan), location)
+            }
+            _ => to_html!(loan),
+        };
+        match self.polonius_info.loan_kinds.get(&loan) {
+            Some(kind) if configuration::dump_show_loan_kinds() => {
+                format!("{} {}", escape_html!(kind.symbol()), text)
+            }
+            _ => text,
+        }
+    }
+
+    /// Format the loans as a sorted list, marking the fake loans with
+    /// the kind of move that created them.
+    fn loans_to_html(&self, loans: &[facts::Loan]) -> String {
+        let mut loans: Vec<_> = loans
+            .iter()
+            .map(|loan| {
+                if self.polonius_info.reference_moves.contains(loan) {
+                    format!("{} [ref-move]", self.loan_to_html(*loan))
+                } else if self.polonius_info.argument_moves.contains(loan) {
+                    format!("{} [arg-move]", self.loan_to_html(*loan))
+                } else {
+                    self.loan_to_html(*loan)
+                }
+            })
+            .collect();
+        loans.sort();
+        join_truncated(loans)
+    }
+
+    fn visit_terminator(&self, bb: mir::BasicBlock, terminator: &mir::Terminator<'tcx>) -> Result<(),io::Error> {
         use rustc::mir::TerminatorKind;
         match terminator.kind {
             TerminatorKind::Goto { target } => {
                 write_edge!(self, bb, target);
             }
             TerminatorKind::SwitchInt { ref targets, .. } => {
-                for target in targets {
-                    write_edge!(self, bb, target);
+                for (index, target) in targets.iter().enumerate() {
+                    write_edge!(self, bb, target, self.edge_label(terminator, index));
                 }
             }
             TerminatorKind::Resume => {
@@ -387,17 +3224,17 @@ impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
                 }
             }
             TerminatorKind::Assert { target, cleanup, .. } => {
-                write_edge!(self, bb, target);
+                write_edge!(self, bb, target, self.edge_label(terminator, 0));
                 if let Some(target) = cleanup {
-                    write_edge!(self, bb, unwind target);
+                    write_edge!(self, bb, unwind target, self.edge_label(terminator, 1));
                 }
             }
             TerminatorKind::Yield { .. } => { unimplemented!() }
             TerminatorKind::GeneratorDrop => { unimplemented!() }
             TerminatorKind::FalseEdges { ref real_target, ref imaginary_targets } => {
-                write_edge!(self, bb, real_target);
-                for target in imaginary_targets {
-                    write_edge!(self, bb, imaginary target);
+                write_edge!(self, bb, real_target, self.edge_label(terminator, 0));
+                for (index, target) in imaginary_targets.iter().enumerate() {
+                    write_edge!(self, bb, imaginary target, self.edge_label(terminator, index + 1));
                 }
             }
             TerminatorKind::FalseUnwind { real_target, unwind } => {
@@ -414,14 +3251,86 @@ impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
 /// Definitely initialized analysis.
 impl<'a, 'tcx> MirInfoPrinter<'a, 'tcx> {
 
-    fn get_definitely_initialized_before_block(&self, bb: mir::BasicBlock) -> String {
+    fn get_definitely_initialized_before_block(&self, bb: mir::BasicBlock) -> (String, Option<String>) {
         let place_set = self.initialization.get_before_block(bb);
-        to_sorted_string!(place_set)
+        match configuration::dump_init_display() {
+            InitDisplay::Full | InitDisplay::Diff => to_sorted_list!(place_set),
+            InitDisplay::Count => (place_set.iter().count().to_string(), None),
+        }
     }
 
 
-    fn get_definitely_initialized_after_statement(&self, location: mir::Location) -> String {
+    fn get_definitely_initialized_after_statement(
+        &self,
+        location: mir::Location,
+    ) -> (String, Option<String>) {
         let place_set = self.initialization.get_after_statement(location);
-        to_sorted_string!(place_set)
+        match configuration::dump_init_display() {
+            InitDisplay::Full => to_sorted_list!(place_set),
+            InitDisplay::Count => (place_set.iter().count().to_string(), None),
+            InitDisplay::Diff => {
+                let previous_set = if location.statement_index == 0 {
+                    self.initialization.get_before_block(location.block)
+                } else {
+                    self.initialization.get_after_statement(mir::Location {
+                        block: location.block,
+                        statement_index: location.statement_index - 1,
+                    })
+                };
+                let current: HashSet<_> = place_set.iter().collect();
+                let previous: HashSet<_> = previous_set.iter().collect();
+                let mut changes: Vec<_> = current.difference(&previous)
+                    .map(|place| format!("+{}", to_html!(place)))
+                    .chain(previous.difference(&current)
+                        .map(|place| format!("-{}", to_html!(place))))
+                    .collect();
+                changes.sort_by(|a, b| a[1..].cmp(&b[1..]));
+                join_truncated_list(changes)
+            }
+        }
+    }
+
+    /// Format the places of the set together with their move paths, for
+    /// example `_1.0: mp3`. Places without an own move path are shown with
+    /// the closest enclosing one, for example `_1.0: in mp1`.
+    fn move_paths_to_html(&self, place_set: &PlaceSet<'tcx>) -> (String, Option<String>) {
+        let move_data = self.move_data.as_ref().unwrap();
+        let mut items: Vec<_> = place_set
+            .iter()
+            .map(|place| {
+                let move_path = match move_data.rev_lookup.find(place) {
+                    LookupResult::Exact(index) => format!("{:?}", index),
+                    LookupResult::Parent(Some(index)) => format!("in {:?}", index),
+                    LookupResult::Parent(None) => String::from("none"),
+                };
+                format!("{}: {}", to_html!(place), move_path)
+            })
+            .collect();
+        items.sort();
+        join_truncated_list(items)
+    }
+
+    /// Write the cell with the definitely uninitialized places after the
+    /// statement if `DUMP_SHOW_UNINITIALIZED` is set.
+    fn write_definitely_uninitialized_after_statement(
+        &self,
+        location: mir::Location,
+    ) -> Result<(),io::Error> {
+        if let Some(ref uninitialization) = self.uninitialization {
+            write_cell!(self, list to_sorted_list!(uninitialization.get_after_statement(location)));
+        }
+        Ok(())
+    }
+
+    /// Write the cell with the maybe initialized places after the
+    /// statement if `DUMP_SHOW_MAYBE_INITIALIZED` is set.
+    fn write_maybe_initialized_after_statement(
+        &self,
+        location: mir::Location,
+    ) -> Result<(),io::Error> {
+        if let Some(ref maybe_initialization) = self.maybe_initialization {
+            write_cell!(self, list to_sorted_list!(maybe_initialization.get_after_statement(location)));
+        }
+        Ok(())
     }
 }
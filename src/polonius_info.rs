@@ -2,15 +2,21 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use log::debug;
+use log::{debug, info, warn};
 use rustc::hir::def_id::DefId;
 use rustc::mir;
 use rustc::ty;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use super::borrowck::{facts, regions};
+use super::configuration;
+use super::mir_analyses::utils::is_prefix;
 use polonius_engine::{Algorithm, Output, Atom};
+use rustc_data_structures::indexed_vec::Idx;
+use std::io;
 use std::path::PathBuf;
+use syntax_pos::Span;
 
+/// The assignment that created a loan: `dest = source` at `location`.
 #[derive(Clone, Debug)]
 pub struct LoanPlaces<'tcx> {
     pub dest: mir::Place<'tcx>,
@@ -18,11 +24,71 @@ pub struct LoanPlaces<'tcx> {
     pub location: mir::Location,
 }
 
+/// The kind of the borrow that created a loan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoanKind {
+    /// A shared borrow `&place`. Shallow borrows of match scrutinees are
+    /// also classified as shared.
+    Shared,
+    /// A mutable borrow `&mut place`. Unique borrows of closure captures
+    /// are also classified as mutable.
+    Mutable,
+    /// A mutable borrow that is only activated at its first use, for
+    /// example the receiver of `vec.push(vec.len())`.
+    TwoPhase,
+}
+
+impl LoanKind {
+    fn from_borrow_kind(kind: mir::BorrowKind) -> Self {
+        match kind {
+            mir::BorrowKind::Shared | mir::BorrowKind::Shallow => LoanKind::Shared,
+            mir::BorrowKind::Unique => LoanKind::Mutable,
+            mir::BorrowKind::Mut { allow_two_phase_borrow: false } => LoanKind::Mutable,
+            mir::BorrowKind::Mut { allow_two_phase_borrow: true } => LoanKind::TwoPhase,
+        }
+    }
+
+    /// The symbol that is shown in front of the loan in the dump.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LoanKind::Shared => "&",
+            LoanKind::Mutable => "&mut",
+            LoanKind::TwoPhase => "2φ",
+        }
+    }
+}
+
+/// The Polonius facts of a single function together with the computed
+/// borrow checker output.
+///
+/// All facts refer to the program points of the validated MIR of the
+/// function (the MIR passed to the constructor) and all point indices in
+/// `borrowck_in_facts` and `borrowck_out_facts` are interned by
+/// `interner`. In addition to the facts emitted by rustc, the input facts
+/// contain fake loans for moved references (see `reference_moves` and
+/// `argument_moves`). The output facts are computed with the algorithm
+/// selected by `POLONIUS_ALGORITHM`.
 pub struct PoloniusInfo {
-    pub(crate) borrowck_in_facts: facts::AllInputFacts,
-    pub(crate) borrowck_out_facts: facts::AllOutputFacts,
-    pub(crate) interner: facts::Interner,
+    pub borrowck_in_facts: facts::AllInputFacts,
+    pub borrowck_out_facts: facts::AllOutputFacts,
+    pub interner: facts::Interner,
     pub variable_regions: HashMap<mir::Local, facts::Region>,
+    /// False if the renumber MIR file was missing, in which case
+    /// `variable_regions` is empty.
+    pub variable_regions_loaded: bool,
+    /// Fake loans created for moves of references.
+    pub reference_moves: Vec<facts::Loan>,
+    /// Fake loans created for references moved into function calls.
+    pub argument_moves: Vec<facts::Loan>,
+    /// The span and the source code of the expression that created the loan.
+    pub loan_spans: HashMap<facts::Loan, (Span, String)>,
+    /// The kinds of the loans created by borrow statements. The fake loans
+    /// have no kind.
+    pub loan_kinds: HashMap<facts::Loan, LoanKind>,
+    /// `region_live_at` indexed by the point.
+    regions_live_at_point: HashMap<facts::PointIndex, Vec<facts::Region>>,
+    /// `borrow_region` indexed by the point.
+    borrow_regions_at_point: HashMap<facts::PointIndex, Vec<(facts::Region, facts::Loan)>>,
 }
 
 /// Returns moves and argument moves that were turned into fake reborrows.
@@ -73,7 +139,13 @@ fn add_fake_facts<'a, 'tcx:'a>(
                     }
                     match place {
                         mir::Place::Local(local) => {
-                            if let Some(var_region) = variable_regions.get(&local) {
+                            // Only references are reborrowed; other types
+                            // with regions (for example, `Copy` structs
+                            // containing references) are not.
+                            let is_reference = mir.local_decls[local].ty.is_region_ptr();
+                            if !is_reference {
+                                debug!("Skipping non-reference destination {:?}", local);
+                            } else if let Some(var_region) = variable_regions.get(&local) {
                                 debug!("var_region = {:?} loan = {}", var_region, last_loan_id);
                                 let loan = facts::Loan::from(last_loan_id);
                                 borrow_region.push(
@@ -108,7 +180,49 @@ fn add_fake_facts<'a, 'tcx:'a>(
     (reference_moves, argument_moves)
 }
 
+/// Does `place` go through the reference stored in `reference`, as in
+/// `(*reference).f`?
+fn goes_through(place: &mir::Place, reference: &mir::Place) -> bool {
+    match place {
+        mir::Place::Projection(box mir::Projection { base, elem }) => {
+            match elem {
+                mir::ProjectionElem::Deref if base == reference => true,
+                _ => goes_through(base, reference),
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Find the assignments that created the loans of `borrow_region`.
+fn find_loan_places<'tcx>(
+    all_facts: &facts::AllInputFacts,
+    interner: &facts::Interner,
+    mir: &mir::Mir<'tcx>,
+) -> HashMap<facts::Loan, LoanPlaces<'tcx>> {
+    let mut loan_places = HashMap::new();
+    for &(_, loan, point) in all_facts.borrow_region.iter() {
+        let location = interner.get_point(point).location;
+        let statements = &mir[location.block].statements;
+        if location.statement_index >= statements.len() {
+            continue;
+        }
+        if let mir::StatementKind::Assign(ref dest, ref source) =
+                statements[location.statement_index].kind {
+            loan_places.insert(loan, LoanPlaces {
+                dest: dest.clone(),
+                source: (**source).clone(),
+                location: location,
+            });
+        }
+    }
+    loan_places
+}
+
 impl PoloniusInfo {
+    /// Read the facts of the function from the `nll-facts` directory and
+    /// the regions of its local variables from the renumber MIR dump in
+    /// `log/mir`, both relative to the current directory.
     pub fn new<'a, 'tcx: 'a>(tcx: ty::TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId, mir: &'a mir::Mir<'tcx>) -> Self {
         // Read Polonius facts.
         let def_path = tcx.hir().def_path(def_id);
@@ -122,28 +236,271 @@ impl PoloniusInfo {
             "log/mir/rustc.{}.-------.renumber.0.mir",
             def_path.to_filename_friendly_no_crate()));
         debug!("Renumber path: {:?}", renumber_path);
-        let variable_regions = regions::load_variable_regions(&renumber_path).unwrap();
+        let (variable_regions, variable_regions_loaded) =
+            match regions::load_variable_regions(&renumber_path) {
+                Ok(variable_regions) => (variable_regions, true),
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                    warn!("The renumber MIR file {:?} is missing, so the regions cannot be \
+                           matched with variables. Run the compiler with \
+                           -Zdump-mir=renumber -Zdump-mir-dir=log/mir/ to create it.",
+                          renumber_path);
+                    (HashMap::new(), false)
+                }
+                Err(error) => panic!("Unable to read {:?}: {}", renumber_path, error),
+            };
 
         //let mir = tcx.mir_validated(def_id).borrow();
 
+        let mut info = Self::from_facts(tcx, mir, facts_loader, variable_regions);
+        info.variable_regions_loaded = variable_regions_loaded;
+        info
+    }
+
+    /// Compute the borrow checker output from already loaded facts without
+    /// touching the file system. `variable_regions` maps the local
+    /// variables to the regions in their types and may be empty, in which
+    /// case no fake loans are created for moved references.
+    pub fn from_facts<'a, 'tcx: 'a>(
+        tcx: ty::TyCtxt<'a, 'tcx, 'tcx>,
+        mir: &'a mir::Mir<'tcx>,
+        facts_loader: facts::FactLoader,
+        variable_regions: HashMap<mir::Local, facts::Region>
+    ) -> Self {
+        let variable_regions_loaded = !variable_regions.is_empty();
         let mut call_magic_wands = HashMap::new();
 
         let mut all_facts = facts_loader.facts;
-        let (_reference_moves, _argument_moves) = add_fake_facts(
+        let (reference_moves, argument_moves) = add_fake_facts(
             &mut all_facts, &facts_loader.interner, &mir,
             &variable_regions, &mut call_magic_wands);
 
-        let output = Output::compute(&all_facts, Algorithm::Naive, true);
+        let output = Output::compute(&all_facts, configuration::polonius_algorithm(), true);
 
         let interner = facts_loader.interner;
+        if configuration::dump_verify_algorithms() {
+            verify_algorithms(&all_facts, &interner, &output);
+        }
+
+        let mut loan_spans = HashMap::new();
+        for &(_, loan, point) in all_facts.borrow_region.iter() {
+            let location = interner.get_point(point).location;
+            let span = mir.source_info(location).span;
+            let snippet = tcx.sess.source_map().span_to_snippet(span).unwrap_or_default();
+            loan_spans.insert(loan, (span, snippet));
+        }
+
+        let loan_kinds = find_loan_places(&all_facts, &interner, mir)
+            .into_iter()
+            .filter(|(loan, _)| !reference_moves.contains(loan) && !argument_moves.contains(loan))
+            .filter_map(|(loan, places)| match places.source {
+                mir::Rvalue::Ref(_, kind, _) => Some((loan, LoanKind::from_borrow_kind(kind))),
+                _ => None,
+            })
+            .collect();
+
+        let mut regions_live_at_point: HashMap<_, Vec<_>> = HashMap::new();
+        for &(region, point) in all_facts.region_live_at.iter() {
+            regions_live_at_point.entry(point).or_default().push(region);
+        }
+        let mut borrow_regions_at_point: HashMap<_, Vec<_>> = HashMap::new();
+        for &(region, loan, point) in all_facts.borrow_region.iter() {
+            borrow_regions_at_point.entry(point).or_default().push((region, loan));
+        }
 
-        let info = Self {
+        Self {
             borrowck_in_facts: all_facts,
             borrowck_out_facts: output,
             interner: interner,
             variable_regions: variable_regions,
+            variable_regions_loaded: variable_regions_loaded,
+            reference_moves: reference_moves,
+            argument_moves: argument_moves,
+            loan_spans: loan_spans,
+            loan_kinds: loan_kinds,
+            regions_live_at_point: regions_live_at_point,
+            borrow_regions_at_point: borrow_regions_at_point,
+        }
+    }
+
+    /// The index of the mid point of the statement at `location`. The mid
+    /// point is where the effects of the statement are checked, so the
+    /// accessors below report the facts that hold while it executes.
+    fn mid_point(&self, location: mir::Location) -> Option<facts::PointIndex> {
+        self.interner.find_point_index(&facts::Point {
+            location: location,
+            typ: facts::PointType::Mid,
+        })
+    }
+
+    /// The loans that are alive at the mid point of the statement at
+    /// `location`. Empty if the location has no facts.
+    pub fn loans_live_at(&self, location: mir::Location) -> &[facts::Loan] {
+        self.mid_point(location)
+            .and_then(|point| self.borrowck_out_facts.borrow_live_at.get(&point))
+            .map(|loans| &loans[..])
+            .unwrap_or(&[])
+    }
+
+    /// The regions that are alive at the mid point of the statement at
+    /// `location`. Empty if the location has no facts.
+    pub fn regions_live_at(&self, location: mir::Location) -> &[facts::Region] {
+        self.mid_point(location)
+            .and_then(|point| self.regions_live_at_point.get(&point))
+            .map(|regions| &regions[..])
+            .unwrap_or(&[])
+    }
+
+    /// The loans created at the mid point of the statement at `location`
+    /// together with their regions, including the fake ones.
+    pub fn borrow_regions_at(&self, location: mir::Location) -> &[(facts::Region, facts::Loan)] {
+        self.mid_point(location)
+            .and_then(|point| self.borrow_regions_at_point.get(&point))
+            .map(|borrow_regions| &borrow_regions[..])
+            .unwrap_or(&[])
+    }
+
+    /// The number of facts of each input relation, including the fake ones.
+    pub fn get_fact_counts(&self) -> BTreeMap<&'static str, usize> {
+        let facts = &self.borrowck_in_facts;
+        let mut counts = BTreeMap::new();
+        counts.insert("borrow_region", facts.borrow_region.len());
+        counts.insert("universal_region", facts.universal_region.len());
+        counts.insert("cfg_edge", facts.cfg_edge.len());
+        counts.insert("killed", facts.killed.len());
+        counts.insert("outlives", facts.outlives.len());
+        counts.insert("region_live_at", facts.region_live_at.len());
+        counts.insert("invalidates", facts.invalidates.len());
+        counts
+    }
+
+    /// The number of distinct loans, including the fake ones.
+    pub fn loan_count(&self) -> usize {
+        let loans: HashSet<_> = self.borrowck_in_facts
+            .borrow_region
+            .iter()
+            .map(|&(_, loan, _)| loan)
+            .collect();
+        loans.len()
+    }
+
+    /// For each loan, find the first and the last program point at which
+    /// it is alive. The loans are sorted by their creation points.
+    pub fn get_loan_intervals(&self) -> Vec<(facts::Loan, facts::Point, facts::Point)> {
+        let mut intervals: HashMap<facts::Loan, (facts::Point, facts::Point)> = HashMap::new();
+        for (&point_index, loans) in self.borrowck_out_facts.borrow_live_at.iter() {
+            let point = self.interner.get_point(point_index);
+            for loan in loans {
+                let interval = intervals
+                    .entry(*loan)
+                    .or_insert_with(|| (point.clone(), point.clone()));
+                if point_key(point) < point_key(&interval.0) {
+                    interval.0 = point.clone();
+                }
+                if point_key(point) > point_key(&interval.1) {
+                    interval.1 = point.clone();
+                }
+            }
+        }
+        self.get_loans_by_creation()
+            .into_iter()
+            .filter_map(|loan| {
+                intervals.remove(&loan).map(|(first, last)| (loan, first, last))
+            })
+            .collect()
+    }
+
+    /// All loans sorted by the program points at which they are created.
+    pub fn get_loans_by_creation(&self) -> Vec<facts::Loan> {
+        let mut loans: Vec<_> = self.borrowck_in_facts
+            .borrow_region
+            .iter()
+            .map(|&(_, loan, point_index)| (point_key(self.interner.get_point(point_index)), loan))
+            .collect();
+        loans.sort();
+        let mut seen = HashSet::new();
+        loans
+            .into_iter()
+            .map(|(_, loan)| loan)
+            .filter(|loan| seen.insert(*loan))
+            .collect()
+    }
+
+    /// The location of the statement that created the loan, or `None` if
+    /// the loan does not appear in the `borrow_region` facts.
+    pub fn loan_source_location(&self, loan: facts::Loan) -> Option<mir::Location> {
+        self.borrowck_in_facts
+            .borrow_region
+            .iter()
+            .find(|&&(_, created_loan, _)| created_loan == loan)
+            .map(|&(_, _, point)| self.interner.get_point(point).location)
+    }
+
+    /// Find the pairs of loans that are live at the same point while at
+    /// least one of them is a mutable borrow and their borrowed places
+    /// overlap. The first loan of each pair is the smaller one and the result
+    /// is sorted by the point.
+    ///
+    /// A reborrow, which borrows a place behind the reference created by the
+    /// other loan (`&mut *a`), does not conflict with it. Two-phase borrows
+    /// are not considered mutable, because shared borrows may be live until
+    /// they are activated. Loans without places, such as the fake ones, never
+    /// conflict.
+    pub fn find_conflicting_loans<'tcx>(
+        &self,
+        loan_places: &HashMap<facts::Loan, LoanPlaces<'tcx>>,
+    ) -> Vec<(facts::Loan, facts::Loan, facts::PointIndex)> {
+        let is_mutable = |loan: &facts::Loan| {
+            self.loan_kinds.get(loan) == Some(&LoanKind::Mutable)
         };
-        info
+        let borrowed_place = |loan: &facts::Loan| {
+            match loan_places.get(loan).map(|places| &places.source) {
+                Some(mir::Rvalue::Ref(_, _, place)) => Some(place),
+                _ => None,
+            }
+        };
+        let is_reborrow = |loan: &facts::Loan, other: &facts::Loan| {
+            match (borrowed_place(loan), loan_places.get(other)) {
+                (Some(place), Some(other_places)) => goes_through(place, &other_places.dest),
+                _ => false,
+            }
+        };
+        let overlap = |first: &facts::Loan, second: &facts::Loan| {
+            match (borrowed_place(first), borrowed_place(second)) {
+                (Some(first_place), Some(second_place)) => {
+                    (is_prefix(first_place, second_place) || is_prefix(second_place, first_place)) &&
+                        !is_reborrow(first, second) && !is_reborrow(second, first)
+                }
+                _ => false,
+            }
+        };
+        let mut conflicts = Vec::new();
+        for (&point, loans) in self.borrowck_out_facts.borrow_live_at.iter() {
+            let mut loans = loans.clone();
+            loans.sort();
+            loans.dedup();
+            for (i, first) in loans.iter().enumerate() {
+                for second in loans[i + 1..].iter() {
+                    if (is_mutable(first) || is_mutable(second)) && overlap(first, second) {
+                        conflicts.push((*first, *second, point));
+                    }
+                }
+            }
+        }
+        conflicts.sort_by_key(|&(first, second, point)| {
+            (point_key(self.interner.get_point(point)), first, second)
+        });
+        conflicts
+    }
+
+    /// Find the assignments that created the loans. Loans that are not
+    /// created by an assignment, for example the fake loans of references
+    /// moved into function calls, are omitted. `mir` must be the MIR
+    /// passed to the constructor, because the facts refer to its points.
+    pub fn get_loan_places<'tcx>(
+        &self,
+        mir: &mir::Mir<'tcx>,
+    ) -> HashMap<facts::Loan, LoanPlaces<'tcx>> {
+        find_loan_places(&self.borrowck_in_facts, &self.interner, mir)
     }
 
     /// Find a variable that has the given region in its type.
@@ -160,6 +517,67 @@ impl PoloniusInfo {
 
 }
 
+/// The loans alive at each point, with the points and the loans sorted.
+fn normalize_borrow_live_at(
+    output: &facts::AllOutputFacts,
+) -> BTreeMap<facts::PointIndex, Vec<facts::Loan>> {
+    output.borrow_live_at
+        .iter()
+        .filter(|(_, loans)| !loans.is_empty())
+        .map(|(&point, loans)| {
+            let mut loans = loans.clone();
+            loans.sort();
+            loans.dedup();
+            (point, loans)
+        })
+        .collect()
+}
+
+/// Compute the output with the `Naive` and the `DatafrogOpt` algorithms
+/// and panic if they disagree on the loans alive at some point. `output` is
+/// the output computed with `POLONIUS_ALGORITHM`, which is reused if it is
+/// one of the two. Both runs use the same interned facts, so the loan
+/// indices can be compared directly.
+fn verify_algorithms(
+    all_facts: &facts::AllInputFacts,
+    interner: &facts::Interner,
+    output: &facts::AllOutputFacts,
+) {
+    let algorithm = configuration::polonius_algorithm();
+    let naive = match algorithm {
+        Algorithm::Naive => normalize_borrow_live_at(output),
+        _ => normalize_borrow_live_at(&Output::compute(all_facts, Algorithm::Naive, false)),
+    };
+    let opt = match algorithm {
+        Algorithm::DatafrogOpt => normalize_borrow_live_at(output),
+        _ => normalize_borrow_live_at(&Output::compute(all_facts, Algorithm::DatafrogOpt, false)),
+    };
+    if naive == opt {
+        info!("Naive and DatafrogOpt agree on borrow_live_at at {} points", naive.len());
+        return;
+    }
+    let points: BTreeSet<_> = naive.keys().chain(opt.keys()).cloned().collect();
+    let mut points: Vec<_> = points.into_iter().collect();
+    points.sort_by_key(|&point| point_key(interner.get_point(point)));
+    let mut differences = Vec::new();
+    for point in points {
+        let naive_loans = naive.get(&point).map(|loans| &loans[..]).unwrap_or(&[]);
+        let opt_loans = opt.get(&point).map(|loans| &loans[..]).unwrap_or(&[]);
+        if naive_loans != opt_loans {
+            differences.push(format!("  {}: Naive {:?}, DatafrogOpt {:?}",
+                                     interner.get_point(point), naive_loans, opt_loans));
+        }
+    }
+    panic!("Naive and DatafrogOpt disagree on borrow_live_at:\n{}", differences.join("\n"));
+}
+
+/// A key for ordering program points by their position in the MIR.
+fn point_key(point: &facts::Point) -> (usize, usize, bool) {
+    (point.location.block.index(),
+     point.location.statement_index,
+     point.typ == facts::PointType::Mid)
+}
+
 /// Check if the statement is assignment.
 fn is_assignment<'tcx>(mir: &mir::Mir<'tcx>,
                        location: mir::Location) -> bool {
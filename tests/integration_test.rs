@@ -0,0 +1,835 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The path of a test program in `tests/verify`.
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/verify").join(name)
+}
+
+/// Create an empty directory in which a test runs the driver. The tests run
+/// in parallel and the driver writes into `nll-facts` in the current
+/// directory, so each test needs its own one.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/integration_tests").join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A command that runs the driver in `dir` on the test program in
+/// `tests/verify/pass`.
+fn dump(dir: &Path, program: &str) -> Command {
+    let mut command = Command::cargo_bin("mir-dump-driver").unwrap();
+    command
+        .current_dir(dir)
+        .arg(fixture(&format!("pass/{}", program)))
+        .env("MIR_DUMP_FULL_COMPILATION", "false");
+    command
+}
+
+/// The cells of the table rows in the DOT graph together with the basic
+/// block to which they belong. The driver writes every row and every cell on
+/// a separate line.
+fn table_rows(graph: &str) -> Vec<(String, Vec<String>)> {
+    let mut rows = Vec::new();
+    let mut block = String::new();
+    let mut row = None;
+    for line in graph.lines() {
+        if line.starts_with('"') && line.contains(" [ shape = ") {
+            block = line[1..].split('"').next().unwrap().to_string();
+        } else if line == "<tr>" {
+            row = Some(Vec::new());
+        } else if line == "</tr>" {
+            if let Some(cells) = row.take() {
+                rows.push((block.clone(), cells));
+            }
+        } else if let Some(ref mut cells) = row {
+            if line.starts_with("<td") && line.ends_with("</td>") {
+                let start = line.find('>').unwrap() + 1;
+                cells.push(line[start..line.len() - "</td>".len()].to_string());
+            }
+        }
+    }
+    rows
+}
+
+/// The comma-separated items of a table cell.
+fn cell_items(cell: &str) -> Vec<&str> {
+    cell.split(", ").collect()
+}
+
+/// Runs the driver on `simple.rs` and checks the generated graph of `foo`.
+#[test]
+fn simple_end_to_end_test() {
+    let dir = test_dir("simple_end_to_end_test");
+    dump(&dir, "simple.rs")
+        .assert()
+        .success();
+
+    let graph_path = dir.join("nll-facts/foo/graph.dot");
+    assert!(predicate::path::exists().eval(graph_path));
+
+    let graph = fs::read_to_string(graph_path).unwrap();
+    assert!(predicate::str::contains("digraph G").eval(&graph));
+    // Basic blocks are printed with their `Debug` representation.
+    assert!(predicate::str::contains("\"bb0\"").eval(&graph));
+    assert!(predicate::str::contains("\"bb1\"").eval(&graph));
+    for line in graph.lines() {
+        assert!(!line.contains("unimplemented"), "Unexpected line: {}", line);
+    }
+}
+
+/// Exports the facts of `foo` into the stable format, imports them back, and
+/// checks that the round trip preserves them.
+#[test]
+fn facts_export_import_test() {
+    let dir = test_dir("facts_export_import_test");
+    dump(&dir, "simple.rs")
+        .assert()
+        .success();
+
+    let exported_path = dir.join("foo.mir-facts");
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .args(&["export-facts", "nll-facts/foo", "foo.mir-facts"])
+        .assert()
+        .success();
+    let exported = fs::read_to_string(exported_path).unwrap();
+    assert!(predicate::str::starts_with("mir-dump-facts 1\n").eval(&exported));
+    assert!(predicate::str::contains("[cfg_edge] point point\n").eval(&exported));
+
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .args(&["import-facts", "foo.mir-facts", "imported-facts"])
+        .assert()
+        .success();
+    for relation in &["borrow_region", "cfg_edge", "killed", "outlives", "region_live_at"] {
+        let file_name = format!("{}.facts", relation);
+        let original = fs::read_to_string(dir.join("nll-facts/foo").join(&file_name)).unwrap();
+        let imported = fs::read_to_string(dir.join("imported-facts").join(&file_name)).unwrap();
+        assert_eq!(original, imported, "Relation {} changed", relation);
+    }
+}
+
+/// Functions with the same name in different modules get separate outputs.
+#[test]
+fn same_names_test() {
+    let dir = test_dir("same_names_test");
+    dump(&dir, "same_names.rs")
+        .assert()
+        .success();
+
+    let graph_a = fs::read_to_string(dir.join("nll-facts/a-foo/graph.dot")).unwrap();
+    let graph_b = fs::read_to_string(dir.join("nll-facts/b-foo/graph.dot")).unwrap();
+    assert_ne!(graph_a, graph_b);
+}
+
+/// All requested output formats are written next to each other.
+#[test]
+fn output_formats_test() {
+    let dir = test_dir("output_formats_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "dot,json,html")
+        .assert()
+        .success();
+
+    assert!(predicate::path::exists().eval(dir.join("nll-facts/foo/graph.dot")));
+    let json = fs::read_to_string(dir.join("nll-facts/foo/graph.json")).unwrap();
+    assert!(predicate::str::contains("\"block\": \"bb0\"").eval(&json));
+    let html = fs::read_to_string(dir.join("nll-facts/foo/graph.html")).unwrap();
+    assert!(predicate::str::contains("<h2 id=\"bb0\">bb0</h2>").eval(&html));
+}
+
+/// With `DUMP_SHOW_BB_NAVIGATION`, the HTML tables link to the neighbouring
+/// blocks.
+#[test]
+fn bb_navigation_test() {
+    let dir = test_dir("bb_navigation_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "html")
+        .env("MIR_DUMP_DUMP_SHOW_BB_NAVIGATION", "true")
+        .assert()
+        .success();
+
+    let html = fs::read_to_string(dir.join("nll-facts/count/graph.html")).unwrap();
+    assert!(html.contains("<a id=\"BB1\"></a>"));
+    assert!(predicate::str::is_match(r#"← prev blocks: .*<a href="#BB\d+">bb\d+</a>"#)
+        .unwrap().eval(&html));
+    assert!(predicate::str::is_match(r#"→ next blocks: .*<a href="#BB\d+">bb\d+</a>"#)
+        .unwrap().eval(&html));
+    assert!(html.contains("<a href=\"#top\">back to top</a>"));
+    assert!(html.contains("<nav "));
+}
+
+/// The HTML output fills the placeholders of `DUMP_HTML_TEMPLATE`.
+#[test]
+fn html_template_test() {
+    let dir = test_dir("html_template_test");
+    fs::write(dir.join("template.html"),
+              "<title>{{FUNCTION_NAME}}</title>\n{{FUNCTION_SIGNATURE}}\n{{CRATE_NAME}}\n\
+               {{TIMESTAMP}}\n<script>{{GRAPH_JSON}}</script>\n{{GRAPH_HTML}}\n").unwrap();
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "html")
+        .env("MIR_DUMP_DUMP_HTML_TEMPLATE", "template.html")
+        .assert()
+        .success();
+
+    let html = fs::read_to_string(dir.join("nll-facts/foo/graph.html")).unwrap();
+    let lines: Vec<_> = html.lines().collect();
+    assert_eq!(lines[0], "<title>foo</title>");
+    assert_eq!(lines[1], "fn foo(a: T) -&gt; ()");
+    assert_eq!(lines[2], "simple");
+    assert!(lines[3].parse::<u64>().is_ok(), "Unexpected timestamp: {}", lines[3]);
+    let json = &lines[4]["<script>".len()..lines[4].len() - "</script>".len()];
+    let graph: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(graph["function"], "foo");
+    assert!(html.contains("<h2 id=\"bb0\">bb0</h2>"));
+    assert!(!html.contains("{{"));
+}
+
+/// The DOT output is written to `graph.dot` even if it is not the first
+/// requested format.
+#[test]
+fn output_formats_dot_last_test() {
+    let dir = test_dir("output_formats_dot_last_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "json,dot")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/foo/graph.dot")).unwrap();
+    assert!(predicate::str::contains("digraph G").eval(&graph));
+    let json = fs::read_to_string(dir.join("nll-facts/foo/graph.json")).unwrap();
+    let _: serde_json::Value = serde_json::from_str(&json).unwrap();
+}
+
+/// The JSON output describes every basic block with its statements.
+#[test]
+fn json_output_test() {
+    let dir = test_dir("json_output_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "json")
+        .assert()
+        .success();
+
+    let json = fs::read_to_string(dir.join("nll-facts/foo/graph.json")).unwrap();
+    let graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let blocks = graph["blocks"].as_array().unwrap();
+    assert!(!blocks.is_empty());
+    assert_eq!(blocks[0]["index"], 0);
+    assert_eq!(blocks[0]["block"], "bb0");
+    let statement = &blocks[0]["statements"][0];
+    assert_eq!(statement["location"], "bb0[0]");
+    for key in &["loans_start", "loans_mid", "borrow_regions_start", "borrow_regions_mid",
+                 "regions_start", "regions_mid", "initialized_after"] {
+        assert!(statement[key].is_array(), "Missing {}", key);
+    }
+}
+
+/// Back edges and loop heads are highlighted.
+#[test]
+fn loop_back_edges_test() {
+    let dir = test_dir("loop_back_edges_test");
+    dump(&dir, "loops.rs")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("[color=blue,style=bold]").eval(&graph));
+    assert!(predicate::str::contains(" color = \"green\"").eval(&graph));
+}
+
+/// Without `cfg_edge` most mid points are not in the facts; their cells are
+/// left empty.
+#[test]
+fn disabled_cfg_edge_test() {
+    let dir = test_dir("disabled_cfg_edge_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_LOAD_CFG_EDGE", "false")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("\"bb0\"").eval(&graph));
+}
+
+/// The facts of the validated MIR are not shown next to the optimized MIR.
+#[test]
+fn optimized_mir_test() {
+    let dir = test_dir("optimized_mir_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_MIR_PHASE", "optimized")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("[OPTIMIZED MIR]").eval(&graph));
+    assert!(!graph.contains(">Loans</td>"));
+    assert!(!graph.contains(">Regions</td>"));
+    assert!(!dir.join("nll-facts/count/loan_intervals.txt").exists());
+}
+
+/// The text output has one line of `|`-separated columns per statement.
+#[test]
+fn text_output_test() {
+    let dir = test_dir("text_output_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "text")
+        .assert()
+        .success();
+
+    let text = fs::read_to_string(dir.join("nll-facts/foo/graph.txt")).unwrap();
+    assert!(predicate::str::starts_with("fn ").eval(&text));
+    let statement = text.lines().find(|line| line.starts_with("bb0[0] | ")).unwrap();
+    assert_eq!(statement.split(" | ").count(), 9, "Unexpected line: {}", statement);
+}
+
+/// A `|` inside a statement is escaped, so that it does not split the line
+/// into more columns.
+#[test]
+fn text_output_escape_test() {
+    let dir = test_dir("text_output_escape_test");
+    dump(&dir, "pipes.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "pipes")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "text")
+        .assert()
+        .success();
+
+    let text = fs::read_to_string(dir.join("nll-facts/pipes/graph.txt")).unwrap();
+    let statement = text.lines().find(|line| line.contains(r"a \| b")).unwrap();
+    assert_eq!(statement.split(" | ").count(), 9, "Unexpected line: {}", statement);
+}
+
+/// `DatafrogOpt` computes the same live loans as `Naive`.
+#[test]
+fn polonius_algorithms_test() {
+    let dir = test_dir("polonius_algorithms_test");
+    let mut outputs = Vec::new();
+    for algorithm in &["Naive", "DatafrogOpt"] {
+        dump(&dir, "simple.rs")
+            .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "json")
+            .env("MIR_DUMP_POLONIUS_ALGORITHM", algorithm)
+            .assert()
+            .success();
+        outputs.push(fs::read_to_string(dir.join("nll-facts/foo/graph.json")).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_POLONIUS_ALGORITHM", "Unknown")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "MIR_DUMP_POLONIUS_ALGORITHM: expected Naive, DatafrogOpt, or LocationInsensitive, \
+             got 'Unknown'"));
+}
+
+/// Loans are shown with the locations that created them.
+#[test]
+fn loan_locations_test() {
+    let dir = test_dir("loan_locations_test");
+    dump(&dir, "simple.rs")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/foo/graph.dot")).unwrap();
+    assert!(predicate::str::is_match(r"L\d+@bb\d+\[\d+\]").unwrap().eval(&graph));
+    // The tooltip shows the assignment that created the loan.
+    assert!(predicate::str::is_match(r"L\d+: created at [^&]*: _\d+ = &amp;mut ").unwrap().eval(&graph));
+}
+
+/// The loans are prefixed with the kind of the borrow that created them.
+#[test]
+fn loan_kinds_test() {
+    let dir = test_dir("loan_kinds_test");
+    dump(&dir, "borrows.rs")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/borrow_kinds/graph.dot")).unwrap();
+    assert!(predicate::str::is_match(r"&amp; L\d+").unwrap().eval(&graph));
+    assert!(predicate::str::is_match(r"&amp;mut L\d+").unwrap().eval(&graph));
+    assert!(predicate::str::is_match(r"2φ L\d+").unwrap().eval(&graph));
+}
+
+/// Overlapping loans that are live at the same point are highlighted if one
+/// of them is mutable. Such a program is rejected by the borrow checker, but
+/// it is still dumped.
+#[test]
+fn conflicting_loans_test() {
+    let dir = test_dir("conflicting_loans_test");
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .arg(fixture("fail/conflicts.rs"))
+        .env("MIR_DUMP_FULL_COMPILATION", "false")
+        .env("MIR_DUMP_DUMP_SHOW_CONFLICTING_LOANS", "true")
+        .assert()
+        .failure();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/conflict/graph.dot")).unwrap();
+    assert!(predicate::str::is_match(r"conflict: L\d+ and L\d+").unwrap().eval(&graph));
+}
+
+/// Statements with borrow errors are marked, also when they are truncated.
+#[test]
+fn borrow_error_test() {
+    let dir = test_dir("borrow_error_test");
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .arg(fixture("fail/conflicts.rs"))
+        .env("MIR_DUMP_FULL_COMPILATION", "false")
+        .env("MIR_DUMP_DUMP_MAX_CELL_CHARS", "20")
+        .assert()
+        .failure();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/conflict/graph.dot")).unwrap();
+    assert!(predicate::str::is_match(r"\[BORROW ERROR: L\d+").unwrap().eval(&graph));
+    assert!(graph.contains("bgcolor=\"salmon\""));
+}
+
+/// A reborrow does not conflict with the loan it reborrows.
+#[test]
+fn reborrows_test() {
+    let dir = test_dir("reborrows_test");
+    dump(&dir, "reborrows.rs")
+        .env("MIR_DUMP_DUMP_SHOW_CONFLICTING_LOANS", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/reborrow/graph.dot")).unwrap();
+    assert!(!graph.contains("conflict: "));
+}
+
+/// With `DUMP_OUTPUT_SVG`, the graph is rendered with `dot`. If `dot` is
+/// not installed, the dump still succeeds with a warning.
+#[test]
+fn svg_output_test() {
+    let dir = test_dir("svg_output_test");
+    // Never accept a graph rendered by an earlier run.
+    let svg_path = dir.join("nll-facts/rendered/graph.svg");
+    let _ = fs::remove_file(&svg_path);
+    let output = dump(&dir, "render.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_SVG", "true")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(dir.join("nll-facts/rendered/graph.dot").exists());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(svg_path.exists() || stderr.contains("unable to run dot"));
+}
+
+/// The outlives constraints are written into a separate graph.
+#[test]
+fn region_constraints_test() {
+    let dir = test_dir("region_constraints_test");
+    dump(&dir, "regions.rs")
+        .env("MIR_DUMP_DUMP_EXPORT_REGION_CONSTRAINTS", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/outlives/region_constraints.dot")).unwrap();
+    assert!(predicate::str::is_match(r#""R\d+" -> "R\d+" \[ label = "bb\d+\[\d+\]:(Start|Mid)"#)
+        .unwrap().eval(&graph));
+    assert!(predicate::str::contains("(first)").eval(&graph));
+}
+
+/// `DUMP_VERIFY_ALGORITHMS` checks that `Naive` and `DatafrogOpt` agree.
+#[test]
+fn verify_algorithms_test() {
+    let dir = test_dir("verify_algorithms_test");
+    dump(&dir, "traits.rs")
+        .env("MIR_DUMP_DUMP_VERIFY_ALGORITHMS", "true")
+        .env("RUST_LOG", "mir_dump::polonius_info=info")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Naive and DatafrogOpt agree on borrow_live_at"))
+        .stderr(predicate::str::contains("disagree").not());
+}
+
+/// Invalid and unknown settings are all reported before anything runs.
+#[test]
+fn invalid_settings_test() {
+    let dir = test_dir("invalid_settings_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_SHOW_LOAN_KINDS", "maybe")
+        .env("MIR_DUMP_DUMP_MAX_CELL_CHARS", "many")
+        .env("MIR_DUMP_DUMP_SHOW_NOTHING", "true")
+        .env("MIR_DUMP_DUMP_MIR_PHASE", "borrowck")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "dot,svg")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "MIR_DUMP_DUMP_SHOW_LOAN_KINDS: expected bool, got 'maybe'"))
+        .stderr(predicate::str::contains(
+            "MIR_DUMP_DUMP_MAX_CELL_CHARS: expected a non-negative integer, got 'many'"))
+        .stderr(predicate::str::contains("MIR_DUMP_DUMP_SHOW_NOTHING: unknown setting"))
+        .stderr(predicate::str::contains(
+            "MIR_DUMP_DUMP_MIR_PHASE: expected validated or optimized, got 'borrowck'"))
+        .stderr(predicate::str::contains(
+            "MIR_DUMP_DUMP_OUTPUT_FORMATS: expected dot, json, html, or text, got 'svg'"));
+}
+
+/// A call whose destination is a `Copy` struct containing a reference does
+/// not get a fake loan for its destination; only the call returning a
+/// reference does. The fake destination loans are the only loans without a
+/// kind in the loan summary.
+#[test]
+fn copy_call_destination_test() {
+    let dir = test_dir("copy_call_destination_test");
+    dump(&dir, "copy_call_destination.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "foo")
+        .env("MIR_DUMP_DUMP_SHOW_LOAN_SUMMARY", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/foo/graph.dot")).unwrap();
+    let destination_loans = regex::Regex::new(r"<td>L\d+</td><td>bb\d+\[\d+\]</td><td></td>")
+        .unwrap()
+        .find_iter(&graph)
+        .count();
+    assert_eq!(destination_loans, 1, "Unexpected graph: {}", graph);
+}
+
+/// The init script runs before the settings are read, so it can change and
+/// unset them.
+#[test]
+fn init_script_test() {
+    let dir = test_dir("init_script_test");
+    fs::write(dir.join("init.sh"),
+              "MIR_DUMP_DUMP_OUTPUT_FORMATS=json\nunset MIR_DUMP_DUMP_SHOW_NOTHING\n").unwrap();
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_INIT_SCRIPT", "init.sh")
+        .env("MIR_DUMP_DUMP_SHOW_NOTHING", "true")
+        .assert()
+        .success();
+
+    assert!(dir.join("nll-facts/foo/graph.json").exists());
+    assert!(!dir.join("nll-facts/foo/graph.dot").exists());
+}
+
+/// The loan summary lists every loan with its kind and last live point.
+#[test]
+fn loan_summary_test() {
+    let dir = test_dir("loan_summary_test");
+    dump(&dir, "summary.rs")
+        .env("MIR_DUMP_DUMP_SHOW_LOAN_SUMMARY", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/summarized/graph.dot")).unwrap();
+    assert!(predicate::str::contains("LOAN SUMMARY").eval(&graph));
+    assert!(predicate::str::is_match(r"<td>L\d+</td><td>bb\d+\[\d+\]</td><td>&amp;mut</td>")
+        .unwrap().eval(&graph));
+}
+
+/// Methods are selected by their qualified names as printed by rustc.
+#[test]
+fn trait_methods_test() {
+    let dir = test_dir("trait_methods_test");
+    dump(&dir, "traits.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "<S as T>::foo")
+        .env("MIR_DUMP_DUMP_SUMMARY_FILE", "nll-facts/traits-summary.json")
+        .assert()
+        .success();
+
+    let summary = fs::read_to_string(dir.join("nll-facts/traits-summary.json")).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+    let names: Vec<_> = summary["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|function| function["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["<S as T>::foo"]);
+}
+
+/// `DUMP_REDACT_FUNCTION_NAMES` hides the name of the function in all
+/// outputs.
+#[test]
+fn redact_function_names_test() {
+    let dir = test_dir("redact_function_names_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "foo")
+        .env("MIR_DUMP_DUMP_REDACT_FUNCTION_NAMES", "true")
+        .env("MIR_DUMP_DUMP_SHOW_CONFIG_NODE", "true")
+        .env("MIR_DUMP_DUMP_NODE_ID_FORMAT", "path")
+        .env("MIR_DUMP_DUMP_GENERATE_REPRO", "true")
+        .assert()
+        .success();
+
+    let summary = fs::read_to_string(dir.join("mir-dump-summary.json")).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+    let name = summary["functions"][0]["name"].as_str().unwrap();
+    assert!(name.starts_with("fn_"), "Unexpected name: {}", name);
+    let graph = fs::read_to_string(dir.join("nll-facts/foo/graph.dot")).unwrap();
+    assert!(!graph.contains("foo"));
+    let repro = fs::read_to_string(dir.join("nll-facts/foo/repro.rs")).unwrap();
+    assert!(!repro.contains("foo"));
+}
+
+/// The tooltip of a list cell contains the items that were summarized as
+/// `... +N more`.
+#[test]
+fn truncated_list_tooltip_test() {
+    let dir = test_dir("truncated_list_tooltip_test");
+    dump(&dir, "simple.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "foo")
+        .env("MIR_DUMP_DUMP_MAX_CELL_CHARS", "8")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/foo/graph.dot")).unwrap();
+    assert!(!predicate::str::is_match(r#"title="_[^"]*more""#).unwrap().eval(&graph));
+    assert!(predicate::str::is_match(r#"title="_[^",]*, [^",]+, [^",]+"#).unwrap().eval(&graph));
+}
+
+/// Truncated cells keep their markup.
+#[test]
+fn truncated_markup_test() {
+    let dir = test_dir("truncated_markup_test");
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .arg(fixture("fail/conflicts.rs"))
+        .env("MIR_DUMP_FULL_COMPILATION", "false")
+        .env("MIR_DUMP_DUMP_MAX_CELL_CHARS", "10")
+        .assert()
+        .failure();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/conflict/graph.dot")).unwrap();
+    assert!(graph.contains("<font color=\"red\"><b>[BORROW ER…</b></font>"));
+}
+
+/// The substitutions of calls are truncated to `DUMP_MIR_SUBSTS_TRUNCATE`
+/// characters.
+#[test]
+fn substs_truncate_test() {
+    let dir = test_dir("substs_truncate_test");
+    dump(&dir, "generics.rs")
+        .env("MIR_DUMP_DUMP_MAX_CELL_CHARS", "0")
+        .env("MIR_DUMP_DUMP_MIR_SUBSTS_TRUNCATE", "5")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/call_generic/graph.dot")).unwrap();
+    assert!(predicate::str::contains("<br />[std:…<br />").eval(&graph));
+}
+
+/// Without `DUMP_ALL_FUNCTIONS`, only the selected functions are dumped.
+#[test]
+fn dump_all_functions_test() {
+    let dir = test_dir("dump_all_functions_test");
+    dump(&dir, "selection.rs")
+        .env("MIR_DUMP_DUMP_ALL_FUNCTIONS", "false")
+        .env("MIR_DUMP_DUMP_MIR_PROC_REGEX", "^selected$")
+        .assert()
+        .success();
+
+    // `test_dir` removed the outputs of earlier runs and of other tests, so
+    // the missing graph was not written by this run.
+    assert!(dir.join("nll-facts/selected/graph.dot").exists());
+    assert!(!dir.join("nll-facts/not_selected/graph.dot").exists());
+}
+
+/// All functions whose names match `DUMP_MIR_PROC_REGEX` are dumped.
+#[test]
+fn mir_proc_regex_test() {
+    let dir = test_dir("mir_proc_regex_test");
+    dump(&dir, "prefixes.rs")
+        .env("MIR_DUMP_DUMP_ALL_FUNCTIONS", "false")
+        .env("MIR_DUMP_DUMP_MIR_PROC_REGEX", "^prefix_")
+        .assert()
+        .success();
+
+    assert!(dir.join("nll-facts/prefix_a/graph.dot").exists());
+    assert!(dir.join("nll-facts/prefix_b/graph.dot").exists());
+    assert!(!dir.join("nll-facts/other/graph.dot").exists());
+}
+
+/// The definitely uninitialized places are shown in an additional column.
+#[test]
+fn uninitialized_column_test() {
+    let dir = test_dir("uninitialized_column_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_SHOW_UNINITIALIZED", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("<td>Definitely Uninitialized</td>").eval(&graph));
+    // The return place is assigned only at the end of the function.
+    let rows = table_rows(&graph);
+    let (_, cells) = rows.iter().find(|(block, cells)| block == "bb0" && cells[0] == "0").unwrap();
+    let uninitialized = cells.last().unwrap();
+    assert!(cell_items(uninitialized).contains(&"_0"), "Unexpected cell: {}", uninitialized);
+}
+
+/// The maybe initialized places are shown in an additional column.
+#[test]
+fn maybe_initialized_column_test() {
+    let dir = test_dir("maybe_initialized_column_test");
+    dump(&dir, "maybe_init.rs")
+        .env("MIR_DUMP_DUMP_SHOW_MAYBE_INITIALIZED", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/maybe_moved/graph.dot")).unwrap();
+    assert!(predicate::str::contains("<td>Maybe Initialized</td>").eval(&graph));
+    // After the branches join, `s` is initialized only if it was not moved.
+    let rows = table_rows(&graph);
+    let maybe_moved = rows.iter().any(|(_, cells)| {
+        cells.len() >= 2 &&
+            cell_items(&cells[cells.len() - 1]).contains(&"_2") &&
+            !cell_items(&cells[cells.len() - 2]).contains(&"_2")
+    });
+    assert!(maybe_moved, "`_2` is never only maybe initialized");
+}
+
+/// The loans that die at a statement are struck through.
+#[test]
+fn dying_loans_test() {
+    let dir = test_dir("dying_loans_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_SHOW_DYING_LOANS", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("<td>Dying Loans</td>").eval(&graph));
+    assert!(predicate::str::is_match(r"<s>L\d+").unwrap().eval(&graph));
+}
+
+/// The input facts are exported as CSV files with a header row.
+#[test]
+fn facts_csv_export_test() {
+    let dir = test_dir("facts_csv_export_test");
+    dump(&dir, "selection.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "selected")
+        .env("MIR_DUMP_DUMP_EXPORT_FACTS_CSV", "true")
+        .assert()
+        .success();
+
+    let outlives = fs::read_to_string(dir.join("nll-facts/selected/facts_csv/outlives.csv")).unwrap();
+    assert!(predicate::str::starts_with("function,region1,region2,point\n").eval(&outlives));
+    let cfg_edge = fs::read_to_string(dir.join("nll-facts/selected/facts_csv/cfg_edge.csv")).unwrap();
+    let edge = cfg_edge.lines().find(|line| line.ends_with(",bb0[0]:Start,bb0[0]:Mid")).unwrap();
+    assert!(edge.starts_with("selected,"), "Unexpected line: {}", edge);
+    assert!(!dir.join("nll-facts/not_selected/facts_csv").exists());
+}
+
+/// The `diff` command marks the statements missing in the old dump as added.
+#[test]
+fn diff_command_test() {
+    let dir = test_dir("diff_command_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_OUTPUT_FORMATS", "json")
+        .assert()
+        .success();
+
+    let new_dir = dir.join("diff-new");
+    let old_dir = dir.join("diff-old");
+    fs::create_dir_all(&new_dir).unwrap();
+    fs::create_dir_all(&old_dir).unwrap();
+    let json = fs::read_to_string(dir.join("nll-facts/count/graph.json")).unwrap();
+    fs::write(new_dir.join("graph.json"), &json).unwrap();
+    let mut graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+    graph["blocks"][0]["statements"].as_array_mut().unwrap().remove(0);
+    fs::write(old_dir.join("graph.json"), graph.to_string()).unwrap();
+
+    Command::cargo_bin("mir-dump-driver")
+        .unwrap()
+        .current_dir(&dir)
+        .args(&["diff", "diff-old", "diff-new"])
+        .assert()
+        .success();
+    let diff = fs::read_to_string(new_dir.join("graph_diff.dot")).unwrap();
+    assert_eq!(diff.matches("<tr><td bgcolor=\"palegreen\">").count(), 1);
+    assert!(!diff.contains("lightcoral"));
+}
+
+/// The definitely initialized places are shown with their move paths.
+#[test]
+fn move_paths_test() {
+    let dir = test_dir("move_paths_test");
+    dump(&dir, "loops.rs")
+        .env("MIR_DUMP_DUMP_SHOW_MOVE_PATHS", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/count/graph.dot")).unwrap();
+    assert!(predicate::str::contains("<td>Move Paths</td>").eval(&graph));
+    assert!(predicate::str::is_match(r"_1: mp\d+").unwrap().eval(&graph));
+}
+
+/// Closures are dumped with `DUMP_INCLUDE_CLOSURES` and selected by the
+/// line on which they start.
+#[test]
+fn closures_test() {
+    let dir = test_dir("closures_test");
+    dump(&dir, "closures.rs")
+        .env("MIR_DUMP_DUMP_INCLUDE_CLOSURES", "true")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "apply::closure_at_line_3")
+        .assert()
+        .success();
+
+    let graphs: Vec<_> = fs::read_dir(dir.join("nll-facts"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            name.starts_with("apply") && name.contains("closure")
+        })
+        .filter(|path| path.join("graph.dot").exists())
+        .collect();
+    assert_eq!(graphs.len(), 1, "Unexpected graphs: {:?}", graphs);
+}
+
+/// Closures are named after the qualified enclosing item, and closures that
+/// start on the same line get distinct names.
+#[test]
+fn closure_names_test() {
+    let selections = [
+        ("closure_names_first", "^pair::closure_at_line_10$"),
+        ("closure_names_second", "^pair::closure_at_line_10_2$"),
+        ("closure_names_method", "add_all::closure_at_line_20$"),
+    ];
+    for (name, regex) in selections.iter() {
+        let dir = test_dir(name);
+        dump(&dir, "closures.rs")
+            .env("MIR_DUMP_DUMP_INCLUDE_CLOSURES", "true")
+            .env("MIR_DUMP_DUMP_MIR_PROC_REGEX", regex)
+            .assert()
+            .success();
+
+        let summary = fs::read_to_string(dir.join("mir-dump-summary.json")).unwrap();
+        let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+        let functions = summary["functions"].as_array().unwrap();
+        assert_eq!(functions.len(), 1, "Unexpected functions for {}: {:?}", regex, functions);
+    }
+}
+
+/// The `SwitchInt` edge labels show the matched values as values of the
+/// switched type, so negative cases stay negative.
+#[test]
+fn signed_switch_labels_test() {
+    let dir = test_dir("signed_switch_labels_test");
+    dump(&dir, "switch.rs")
+        .env("MIR_DUMP_DUMP_MIR_PROC", "sign")
+        .env("MIR_DUMP_DUMP_SHOW_EDGE_LABELS", "true")
+        .assert()
+        .success();
+
+    let graph = fs::read_to_string(dir.join("nll-facts/sign/graph.dot")).unwrap();
+    assert!(graph.contains("label=\"-1\""));
+    assert!(!graph.contains("4294967295"));
+}
@@ -0,0 +1,10 @@
+fn conflict(mut x: u32) -> u32 {
+    let a = &mut x;
+    let b = &x; //~ ERROR cannot borrow `x` as immutable because it is also borrowed as mutable
+    *a += *b;
+    x
+}
+
+fn main() {
+    conflict(1);
+}
@@ -0,0 +1,12 @@
+fn borrow_kinds(mut v: Vec<usize>) -> usize {
+    let shared = &v;
+    let len = shared.len();
+    let mutable = &mut v;
+    mutable.push(len);
+    v.push(v.len());
+    v[0]
+}
+
+fn main() {
+    borrow_kinds(vec![1, 2, 3]);
+}
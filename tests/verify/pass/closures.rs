@@ -0,0 +1,30 @@
+fn apply(values: &mut Vec<u32>) {
+    let mut total = 0;
+    values.iter().for_each(|value| {
+        total += *value;
+    });
+    assert!(total == 3);
+}
+
+fn pair(value: u32) -> (u32, u32) {
+    let (double, square) = (|x: u32| x * 2, |x: u32| x * x);
+    (double(value), square(value))
+}
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn add_all(&mut self, values: &[u32]) {
+        values.iter().for_each(|value| self.count += *value);
+    }
+}
+
+fn main() {
+    let mut values = vec![1, 2];
+    apply(&mut values);
+    let _ = pair(3);
+    let mut counter = Counter { count: 0 };
+    counter.add_all(&values);
+}
@@ -0,0 +1,24 @@
+#[derive(Clone, Copy)]
+struct Wrapper<'a> {
+    value: &'a u32,
+}
+
+fn wrap<'a>(value: &'a u32) -> Wrapper<'a> {
+    Wrapper { value: value }
+}
+
+fn unwrap<'a>(x: &'a u32) -> &'a u32 {
+    x
+}
+
+fn foo() {
+    let a = 5;
+    let w = wrap(&a);
+    let v = w;
+    let r = unwrap(&a);
+    assert!(*w.value == *v.value && *r == 5);
+}
+
+fn main() {
+    foo();
+}
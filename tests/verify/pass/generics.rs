@@ -0,0 +1,12 @@
+fn identity<T>(x: T) -> T {
+    x
+}
+
+fn call_generic() -> usize {
+    let v: Vec<Vec<u32>> = Vec::new();
+    identity::<Vec<Vec<u32>>>(v).len()
+}
+
+fn main() {
+    call_generic();
+}
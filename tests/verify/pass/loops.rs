@@ -0,0 +1,13 @@
+fn count(values: &[u32]) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while i < values.len() {
+        sum += values[i];
+        i += 1;
+    }
+    sum
+}
+
+fn main() {
+    assert!(count(&[1, 2, 3]) == 6);
+}
@@ -0,0 +1,12 @@
+fn maybe_moved(flag: bool, s: String) -> usize {
+    if flag {
+        drop(s);
+        0
+    } else {
+        s.len()
+    }
+}
+
+fn main() {
+    maybe_moved(true, String::new());
+}
@@ -0,0 +1,8 @@
+fn pipes() -> usize {
+    let text = "a | b";
+    text.len()
+}
+
+fn main() {
+    assert!(pipes() == 5);
+}
@@ -0,0 +1,19 @@
+fn prefix_a(x: &mut u32) {
+    *x += 1;
+}
+
+fn prefix_b(x: &mut u32) {
+    *x += 2;
+}
+
+fn other(x: &mut u32) {
+    *x += 3;
+}
+
+fn main() {
+    let mut x = 0;
+    prefix_a(&mut x);
+    prefix_b(&mut x);
+    other(&mut x);
+    assert!(x == 6);
+}
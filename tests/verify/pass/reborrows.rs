@@ -0,0 +1,23 @@
+struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+fn reborrow(mut x: u32, counter: &mut Counter) -> u32 {
+    let a = &mut x;
+    let b = &mut *a;
+    *b += 1;
+    counter.increment();
+    counter.increment();
+    *a + counter.value
+}
+
+fn main() {
+    let mut counter = Counter { value: 0 };
+    assert!(reborrow(1, &mut counter) == 4);
+}
@@ -0,0 +1,8 @@
+fn outlives<'a>(x: &'a u32, y: &'a u32) -> &'a u32 {
+    let first = x;
+    if *first > *y { first } else { y }
+}
+
+fn main() {
+    outlives(&1, &2);
+}
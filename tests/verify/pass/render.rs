@@ -0,0 +1,8 @@
+fn rendered(x: &mut u32) -> u32 {
+    let y = &*x;
+    *y + 1
+}
+
+fn main() {
+    rendered(&mut 1);
+}
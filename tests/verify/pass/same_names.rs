@@ -0,0 +1,19 @@
+mod a {
+    pub fn foo(x: &mut u32) {
+        *x = 1;
+    }
+}
+
+mod b {
+    pub fn foo(x: &mut u32) {
+        let y = &mut *x;
+        *y = 2;
+    }
+}
+
+fn main() {
+    let mut x = 0;
+    a::foo(&mut x);
+    b::foo(&mut x);
+    assert!(x == 2);
+}
@@ -0,0 +1,14 @@
+fn selected(x: &mut u32) {
+    *x += 1;
+}
+
+fn not_selected(x: &mut u32) {
+    *x += 2;
+}
+
+fn main() {
+    let mut x = 0;
+    selected(&mut x);
+    not_selected(&mut x);
+    assert!(x == 3);
+}
@@ -0,0 +1,11 @@
+fn summarized(mut x: u32) -> u32 {
+    let shared = &x;
+    let copy = *shared;
+    let mutable = &mut x;
+    *mutable += copy;
+    x
+}
+
+fn main() {
+    summarized(1);
+}
@@ -0,0 +1,11 @@
+fn sign(value: i32) -> i32 {
+    match value {
+        -1 => 1,
+        0 => 0,
+        _ => 2,
+    }
+}
+
+fn main() {
+    assert!(sign(-1) == 1);
+}